@@ -75,7 +75,7 @@ fn benchmark_real_file_density_tree_calculation(c: &mut Criterion) {
     c.bench_function("real_file_density_tree_calculation", |b| {
         b.iter(|| {
             let dtree = DensityTree::from_document(black_box(&document)).unwrap();
-            assert_eq!(dtree.tree.values().len(), 893);
+            assert_eq!(dtree.tree.values().len(), 830);
         })
     });
 }
@@ -98,6 +98,57 @@ fn benchmark_real_file_density_tree_calculation_and_sort(c: &mut Criterion) {
     });
 }
 
+fn benchmark_real_file_sort_vs_top_k_nodes(c: &mut Criterion) {
+    let content = read_file_content_from_zip(
+        "html/pages.zip",
+        "pages/sas-bankruptcy-protection.html",
+    )
+    .unwrap();
+    let document = build_dom(content.as_str());
+    let dtree = DensityTree::from_document(&document).unwrap();
+
+    c.bench_function("real_file_sorted_nodes_top_3", |b| {
+        b.iter(|| {
+            let sorted_nodes = black_box(&dtree).sorted_nodes();
+            let top: Vec<_> = sorted_nodes.iter().rev().take(3).collect();
+            assert_eq!(top[0].density, 104.79147);
+        })
+    });
+
+    c.bench_function("real_file_top_k_nodes_3", |b| {
+        b.iter(|| {
+            let top = black_box(&dtree).top_k_nodes(3);
+            assert_eq!(top[0].density, 104.79147);
+        })
+    });
+}
+
+fn benchmark_real_file_staged_vs_full_density_tree(c: &mut Criterion) {
+    let content = read_file_content_from_zip(
+        "html/pages.zip",
+        "pages/sas-bankruptcy-protection.html",
+    )
+    .unwrap();
+    let document = build_dom(content.as_str());
+
+    c.bench_function("real_file_density_tree_staged", |b| {
+        b.iter(|| {
+            let mut dtree =
+                DensityTree::from_document(black_box(&document)).unwrap();
+            dtree.calculate_density_sum().unwrap();
+            assert_eq!(dtree.tree.values().len(), 830);
+        })
+    });
+
+    c.bench_function("real_file_density_tree_full", |b| {
+        b.iter(|| {
+            let dtree =
+                DensityTree::from_document_full(black_box(&document)).unwrap();
+            assert_eq!(dtree.tree.values().len(), 830);
+        })
+    });
+}
+
 fn benchmark_node_text_extraction(c: &mut Criterion) {
     let content = read_file_content_from_zip(
         "html/pages.zip",
@@ -120,13 +171,45 @@ fn benchmark_node_text_extraction(c: &mut Criterion) {
     });
 }
 
+fn benchmark_real_files_content_extraction(c: &mut Criterion) {
+    let pages = [
+        "pages/aaron-rodgers-completes-darkness-retreat-spt-intl.html",
+        "pages/china-beating-us-nigerian-lithium-rush-race-go-electric.html",
+        "pages/joe-root-unbeaten-as-england-bowlers-bring-new-zealand-to-its-knees.html",
+        "pages/sas-bankruptcy-protection.html",
+        "pages/science-environment-64710225.html",
+    ];
+    let documents: Vec<(scraper::Html, DensityTree)> = pages
+        .iter()
+        .map(|page| {
+            let content =
+                read_file_content_from_zip("html/pages.zip", page).unwrap();
+            let document = build_dom(content.as_str());
+            let dtree = DensityTree::from_document_full(&document).unwrap();
+            (document, dtree)
+        })
+        .collect();
+
+    c.bench_function("real_files_extract_content", |b| {
+        b.iter(|| {
+            for (document, dtree) in &documents {
+                let content = dtree.extract_content(black_box(document)).unwrap();
+                assert!(!content.is_empty());
+            }
+        })
+    });
+}
+
 criterion_group!(
     benches,
     benchmark_test_1_html_dom_content_extaction,
     benchmark_real_file_dom_content_extraction,
     benchmark_real_file_density_tree_calculation,
     benchmark_real_file_density_tree_calculation_and_sort,
+    benchmark_real_file_sort_vs_top_k_nodes,
+    benchmark_real_file_staged_vs_full_density_tree,
     benchmark_node_text_extraction,
+    benchmark_real_files_content_extraction,
 );
 
 criterion_main!(benches);
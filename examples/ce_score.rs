@@ -1,38 +1,21 @@
 use anyhow::{Context, Result};
+use dom_content_extraction::eval::{normalize_text, score_extraction};
 use dom_content_extraction::scraper::Html;
 use dom_content_extraction::DensityTree;
 use rayon::prelude::*;
 use regex::Regex;
 use std::{fs, path::Path};
 
-fn normalize_text(text: &str) -> String {
-    text.split_whitespace().collect::<Vec<&str>>().join(" ")
-}
-
-fn clean_and_normalize_text(text: &str) -> String {
-    // Remove all punctuation except apostrophes
-    let punctuation_regex = Regex::new(r"[^\w\s']").unwrap();
-    let text = punctuation_regex.replace_all(text, " ");
-
-    // Replace multiple spaces with a single space
-    let space_regex = Regex::new(r"\s+").unwrap();
-    let text = space_regex.replace_all(&text, " ");
-
-    // Convert to lowercase
-    let text = text.to_lowercase();
-
-    // Trim leading and trailing spaces
-    text.trim().to_string()
-}
-
 fn extract_content_from_html(file_path: &Path) -> Result<String> {
     let content = fs::read_to_string(file_path)
         .with_context(|| format!("Failed to read file: {:?}", file_path))?;
 
     let document = Html::parse_document(&content);
-    let mut dtree = DensityTree::from_document(&document).unwrap();
-    let _ = dtree.calculate_density_sum();
-    let extracted_content = dtree.extract_content(&document).unwrap();
+    // pages without text (image-only ones) come back as errors and are
+    // skipped instead of aborting the whole run
+    let mut dtree = DensityTree::from_document(&document)?;
+    dtree.calculate_density_sum()?;
+    let extracted_content = dtree.extract_content(&document)?;
 
     Ok(normalize_text(&extracted_content))
 }
@@ -59,43 +42,12 @@ fn clean_txt_file(file_path: &Path) -> Result<String> {
     Ok(content)
 }
 
-fn calculate_lcs(s1: &str, s2: &str) -> usize {
-    let s1: Vec<char> = s1.chars().collect();
-    let s2: Vec<char> = s2.chars().collect();
-    let (m, n) = (s1.len(), s2.len());
-    let mut prev = vec![0; n + 1];
-    let mut curr = vec![0; n + 1];
-
-    for i in 1..=m {
-        for j in 1..=n {
-            if s1[i - 1] == s2[j - 1] {
-                curr[j] = prev[j - 1] + 1;
-            } else {
-                curr[j] = curr[j - 1].max(prev[j]);
-            }
-        }
-        std::mem::swap(&mut prev, &mut curr);
-    }
-
-    prev[n]
-}
-
 fn process_file_pair(txt_path: &Path, html_path: &Path) -> Result<(f64, f64, f64)> {
     let clean_content = clean_txt_file(txt_path)?;
-    let clean_content = clean_and_normalize_text(&clean_content);
-
-    // let extracted_content =
-    //     clean_and_normalize_text(&extract_content_from_html(html_path)?);
-
     let extracted_content = extract_content_from_html(html_path)?;
-    let extracted_content = clean_and_normalize_text(&extracted_content);
-
-    let lcs_length = calculate_lcs(&clean_content, &extracted_content);
-    let precision = lcs_length as f64 / extracted_content.len() as f64;
-    let recall = lcs_length as f64 / clean_content.len() as f64;
-    let f1_score = 2.0 * (precision * recall) / (precision + recall);
+    let scores = score_extraction(&clean_content, &extracted_content);
 
-    Ok((precision, recall, f1_score))
+    Ok((scores.precision, scores.recall, scores.f1))
 }
 
 fn main() -> Result<()> {
@@ -36,8 +36,12 @@ fn process_lorem_ipsum() {
     let document = Html::parse_document(&html_content);
     let mut dtree = DensityTree::from_document(&document).unwrap();
     let _ = dtree.calculate_density_sum(); // do not forget to calculate DS
-    let extracted_content = dtree.extract_content(&document).unwrap();
-    println!("Extracted content:\n{}", extracted_content);
+    match dtree.extract_content(&document) {
+        Ok(extracted_content) => {
+            println!("Extracted content:\n{}", extracted_content)
+        }
+        Err(err) => println!("Nothing extracted: {}", err),
+    }
 }
 
 fn process_test_4_html() {
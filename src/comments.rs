@@ -0,0 +1,228 @@
+//! Comment sections separated from the main content.
+//!
+//! On blog pages a long comment thread can be as dense as the article itself
+//! and gets merged into the extracted content. The thread container is
+//! recognized by its conventional `id` or `class` and dropped from the
+//! density tree before the content is selected.
+use crate::scraper::{ElementRef, Html};
+use crate::selectors::COMMENTS_SELECTOR;
+use crate::{
+    get_node_by_id, get_node_text, has_anchor_parent, BuildOptions, DensityNode,
+    DensityTree, DomExtractionError,
+};
+use ego_tree::iter::Edge;
+use ego_tree::{NodeId, Tree};
+use std::collections::HashMap;
+
+impl DensityTree {
+    /// Extracts the main content and the comment section separately, as
+    /// `(content, comments)`.
+    ///
+    /// The first element with `comments`, `disqus_thread` or `respond` id or
+    /// class under the root of the tree is taken as the comment section, the
+    /// content is then extracted as `extract_content` does, but as if that
+    /// element wasn't in the document. Without a comment section this is the
+    /// same as `extract_content` and `None`. `extract_content` itself is not
+    /// affected, comments are split only when asked for.
+    ///
+    /// As with `extract_content`, `calculate_density_sum` should be called first.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// let mut dtree = DensityTree::from_document(&document)?;
+    /// dtree.calculate_density_sum()?;
+    /// let (content, comments) = dtree.extract_with_comments(&document)?;
+    /// ```
+    pub fn extract_with_comments(
+        &self,
+        document: &Html,
+    ) -> Result<(String, Option<String>), DomExtractionError> {
+        let root_id = self.tree.root().value().node_id;
+        let Some(root) = ElementRef::wrap(get_node_by_id(root_id, document)?)
+        else {
+            return Ok((self.extract_content(document)?, None));
+        };
+        let Some(comments) = root.select(&COMMENTS_SELECTOR).next() else {
+            return Ok((self.extract_content(document)?, None));
+        };
+
+        let comments_text = get_node_text(comments.id(), document)?;
+        let mut dtree = self.without_subtree(comments.id(), document)?;
+        dtree.calculate_density_tree();
+        dtree.calculate_density_sum()?;
+
+        // texts of content nodes are collected from the DOM, where ancestors
+        // of the thread still contain it. Cloning keeps `NodeId`s, so the
+        // tree can be used with the clone as is.
+        let mut without_comments = document.clone();
+        if let Some(mut node) = without_comments.tree.get_mut(comments.id()) {
+            node.detach();
+        }
+        Ok((
+            dtree.extract_content(&without_comments)?,
+            Some(comments_text.trim().to_string()),
+        ))
+    }
+
+    /// Copy of the tree without the density node of the given DOM node and
+    /// its subtree. Metrics of its ancestors are rebuilt from the remaining
+    /// children, densities are left for the caller to recalculate. Works for
+    /// trees built with any `ExtractionOptions`, only element metrics are
+    /// recomputed.
+    fn without_subtree(
+        &self,
+        node_id: NodeId,
+        document: &Html,
+    ) -> Result<Self, DomExtractionError> {
+        let Some(removed) = self
+            .tree
+            .nodes()
+            .find(|node| node.value().node_id == node_id)
+        else {
+            return Ok(Self {
+                tree: self.tree.clone(),
+                rooted_at_element: self.rooted_at_element,
+            });
+        };
+
+        // detached nodes stay in the arena of `ego_tree` and are still
+        // visited by `Tree::nodes`, so the tree is copied without them
+        let mut tree = Tree::new(self.tree.root().value().clone());
+        let mut copied: HashMap<NodeId, NodeId> =
+            HashMap::from([(self.tree.root().id(), tree.root().id())]);
+        let mut in_removed = false;
+        for edge in self.tree.root().traverse().skip(1) {
+            let node = match edge {
+                Edge::Open(node) if node == removed => {
+                    in_removed = true;
+                    continue;
+                }
+                Edge::Close(node) if node == removed => {
+                    in_removed = false;
+                    continue;
+                }
+                Edge::Open(node) if !in_removed => node,
+                _ => continue,
+            };
+            let parent_id = node
+                .parent()
+                .and_then(|parent| copied.get(&parent.id()))
+                .copied()
+                .ok_or(DomExtractionError::NodeAccessError(node.id()))?;
+            let mut parent = tree
+                .get_mut(parent_id)
+                .ok_or(DomExtractionError::NodeAccessError(parent_id))?;
+            let copy_id = parent.append(node.value().clone()).id();
+            copied.insert(node.id(), copy_id);
+        }
+
+        // closest ancestor first, so each one is rebuilt from updated children
+        for ancestor in removed.ancestors() {
+            let ancestor_id = copied
+                .get(&ancestor.id())
+                .copied()
+                .ok_or(DomExtractionError::NodeAccessError(ancestor.id()))?;
+            let dom_node = get_node_by_id(ancestor.value().node_id, document)?;
+            // same order of operations as in `build_density_tree`
+            let mut rebuilt = DensityNode::new(dom_node.id());
+            let copy = tree
+                .get(ancestor_id)
+                .ok_or(DomExtractionError::NodeAccessError(ancestor_id))?;
+            for child in copy.children() {
+                let dom_child = get_node_by_id(child.value().node_id, document)?;
+                rebuilt.merge_child_metrics(
+                    child.value(),
+                    has_anchor_parent(dom_child),
+                );
+            }
+            // ancestors are elements (or the document), their own metrics
+            // are tag counts only, which don't depend on the options the
+            // tree was built with (`char_count_mode` etc. apply to text)
+            rebuilt.add_node_metrics(dom_node.value(), &BuildOptions::default());
+            if let Some(mut copy) = tree.get_mut(ancestor_id) {
+                *copy.value() = rebuilt;
+            }
+        }
+        Ok(Self {
+            tree,
+            rooted_at_element: self.rooted_at_element,
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::scraper::Selector;
+    use crate::tests::{build_dom, read_file};
+    use crate::NodeMetrics;
+
+    fn analyze(document: &Html) -> DensityTree {
+        let mut dtree = DensityTree::from_document(document).unwrap();
+        dtree.calculate_density_sum().unwrap();
+        dtree
+    }
+
+    #[test]
+    fn test_extract_with_comments() {
+        let content = read_file("html/blog_comments.html").unwrap();
+        let document = build_dom(content.as_str());
+        let dtree = analyze(&document);
+
+        // without the split the thread is merged into the content
+        let merged = dtree.extract_content(&document).unwrap();
+        assert!(merged.contains("cobalt"));
+
+        let (content, comments) = dtree.extract_with_comments(&document).unwrap();
+        assert!(content.contains("Miners are racing"));
+        assert!(!content.contains("cobalt"));
+        let comments = comments.unwrap();
+        assert!(comments.contains("cobalt"));
+        assert!(!comments.contains("Miners are racing"));
+    }
+
+    #[test]
+    fn test_extract_without_comments() {
+        let content = read_file("html/article.html").unwrap();
+        let document = build_dom(content.as_str());
+        let dtree = analyze(&document);
+
+        let (content, comments) = dtree.extract_with_comments(&document).unwrap();
+        assert_eq!(content, dtree.extract_content(&document).unwrap());
+        assert!(comments.is_none());
+    }
+
+    #[test]
+    fn test_without_subtree_matches_rebuilt_tree() {
+        let document = build_dom(
+            r#"<html><body><div><p>Some <a href="/">text</a></p>
+            <div id="comments"><p>A <a href="/u">comment</a></p></div>
+            </div></body></html>"#,
+        );
+        let comments = document
+            .select(&Selector::parse("#comments").unwrap())
+            .next()
+            .unwrap();
+        let dtree = analyze(&document);
+        let pruned = dtree.without_subtree(comments.id(), &document).unwrap();
+
+        let expected = build_dom(
+            r#"<html><body><div><p>Some <a href="/">text</a></p>
+            </div></body></html>"#,
+        );
+        let expected = DensityTree::from_document(&expected).unwrap();
+        // whitespace around the thread is merged into a single text node
+        // when it's removed from the source, skip empty nodes
+        let metrics = |dtree: &DensityTree| {
+            dtree
+                .tree
+                .values()
+                .map(|node| node.metrics())
+                .filter(|metrics| *metrics != NodeMetrics::default())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(metrics(&pruned), metrics(&expected));
+    }
+}
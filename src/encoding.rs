@@ -0,0 +1,172 @@
+//! Decoding raw HTML bytes into UTF-8.
+//!
+//! Pages fetched from the network or read from disk are not always UTF-8,
+//! older Cyrillic and CJK sites in particular. The encoding is taken from a
+//! byte order mark or a `<meta>` declaration when there is one, otherwise
+//! it's guessed with `chardetng` (the detector used by Firefox). The bytes
+//! are decoded with `encoding_rs`, malformed sequences are replaced with
+//! U+FFFD.
+pub use encoding_rs::Encoding;
+
+use chardetng::EncodingDetector;
+use encoding_rs::{UTF_16BE, UTF_16LE, UTF_8};
+
+/// How many leading bytes are scanned for a `<meta>` charset declaration,
+/// browsers look at the first 1024 bytes as well.
+const PRESCAN_LENGTH: usize = 1024;
+
+/// Decodes `bytes` into a UTF-8 `String`, returning it together with the
+/// encoding it was decoded from.
+///
+/// A byte order mark wins, then a charset declared with `<meta charset>` or
+/// `<meta http-equiv="Content-Type">` in the first 1024 bytes. Heuristic
+/// detection is used only when neither is present (or the declared label is
+/// unknown), since it can misfire on short pages.
+///
+/// # Examples
+///
+/// ```no_run
+/// let bytes = std::fs::read("page.html")?;
+/// let (html, encoding) = decode_to_utf8(&bytes);
+/// println!("decoded from {}", encoding.name());
+/// let document = Html::parse_document(&html);
+/// ```
+pub fn decode_to_utf8(bytes: &[u8]) -> (String, &'static Encoding) {
+    let encoding = match Encoding::for_bom(bytes) {
+        Some((encoding, _)) => encoding,
+        None => declared_encoding(bytes).unwrap_or_else(|| guess_encoding(bytes)),
+    };
+    // `decode` strips the BOM, if any
+    let (text, encoding, _) = encoding.decode(bytes);
+    (text.into_owned(), encoding)
+}
+
+fn guess_encoding(bytes: &[u8]) -> &'static Encoding {
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    detector.guess(None, true)
+}
+
+/// Looks for a charset in `<meta>` tags within the first `PRESCAN_LENGTH`
+/// bytes. Both `<meta charset="...">` and the `content` attribute of
+/// `<meta http-equiv="Content-Type">` carry it as `charset=...`.
+fn declared_encoding(bytes: &[u8]) -> Option<&'static Encoding> {
+    let head = bytes[..bytes.len().min(PRESCAN_LENGTH)].to_ascii_lowercase();
+    let mut rest = head.as_slice();
+    while let Some(start) = find(rest, b"<meta") {
+        let tag = &rest[start..];
+        let end = tag.iter().position(|&b| b == b'>').unwrap_or(tag.len());
+        if let Some(encoding) = meta_charset(&tag[..end]) {
+            return Some(encoding);
+        }
+        rest = &tag[end..];
+    }
+    None
+}
+
+fn meta_charset(tag: &[u8]) -> Option<&'static Encoding> {
+    let value = &tag[find(tag, b"charset")? + b"charset".len()..];
+    let value = value
+        .trim_ascii_start()
+        .strip_prefix(b"=")?
+        .trim_ascii_start();
+    let value = value
+        .strip_prefix(b"\"")
+        .or_else(|| value.strip_prefix(b"'"))
+        .unwrap_or(value);
+    let end = value
+        .iter()
+        .position(|&b| {
+            matches!(b, b'"' | b'\'' | b';' | b'/') || b.is_ascii_whitespace()
+        })
+        .unwrap_or(value.len());
+    let encoding = Encoding::for_label(&value[..end])?;
+    // a document which could be parsed as ASCII can't really be UTF-16,
+    // browsers treat such a declaration as UTF-8
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        return Some(UTF_8);
+    }
+    Some(encoding)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use encoding_rs::{KOI8_R, WINDOWS_1251};
+
+    #[test]
+    fn test_decode_windows_1251() {
+        let text = "<p>Горнодобывающие компании спешат получить доступ к \
+                    месторождениям лития, так как спрос на батареи растёт.</p>";
+        let (bytes, _, had_errors) = WINDOWS_1251.encode(text);
+        assert!(!had_errors);
+
+        let (decoded, encoding) = decode_to_utf8(&bytes);
+        assert_eq!(decoded, text);
+        assert_eq!(encoding, WINDOWS_1251);
+        assert_eq!(encoding.name(), "windows-1251");
+    }
+
+    #[test]
+    fn test_decode_utf8() {
+        let text = "<p>Miners are racing — Горнодобывающие компании</p>";
+        let (decoded, encoding) = decode_to_utf8(text.as_bytes());
+        assert_eq!(decoded, text);
+        assert_eq!(encoding, UTF_8);
+    }
+
+    #[test]
+    fn test_decode_declared_charset() {
+        // too short for the detector, it guesses windows-1252
+        let html = "<html><head><meta charset=\"windows-1251\"></head>\
+                    <body><p>Да</p></body></html>";
+        let (bytes, _, _) = WINDOWS_1251.encode(html);
+
+        let (decoded, encoding) = decode_to_utf8(&bytes);
+        assert_eq!(encoding, WINDOWS_1251);
+        assert_eq!(decoded, html);
+    }
+
+    #[test]
+    fn test_decode_http_equiv_charset() {
+        let html = "<html><head><meta http-equiv=\"Content-Type\" \
+                    content=\"text/html; charset=KOI8-R\"></head>\
+                    <body><p>Да</p></body></html>";
+        let (bytes, _, _) = KOI8_R.encode(html);
+
+        let (decoded, encoding) = decode_to_utf8(&bytes);
+        assert_eq!(encoding, KOI8_R);
+        assert_eq!(decoded, html);
+    }
+
+    #[test]
+    fn test_decode_unknown_charset_falls_back() {
+        let text = "<meta charset=\"no-such-charset\"><p>Горнодобывающие \
+                    компании спешат получить доступ к месторождениям лития.</p>";
+        let (bytes, _, _) = WINDOWS_1251.encode(text);
+
+        let (decoded, encoding) = decode_to_utf8(&bytes);
+        assert_eq!(encoding, WINDOWS_1251);
+        assert_eq!(decoded, text);
+    }
+
+    #[test]
+    fn test_decode_bom() {
+        // BOM wins over the declaration
+        let mut bytes = vec![0xEF, 0xBB, 0xBF];
+        bytes.extend_from_slice(
+            "<meta charset=\"windows-1251\"><p>Да</p>".as_bytes(),
+        );
+
+        let (decoded, encoding) = decode_to_utf8(&bytes);
+        assert_eq!(encoding, UTF_8);
+        assert_eq!(decoded, "<meta charset=\"windows-1251\"><p>Да</p>");
+    }
+}
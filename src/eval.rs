@@ -0,0 +1,366 @@
+//! CleanEval-style evaluation of extraction quality.
+//!
+//! Both gold standard and extracted texts are normalized (punctuation
+//! stripped, whitespace collapsed, lowercased), then compared by the length
+//! of their longest common subsequence of chars (or words, see
+//! [`Tokenization`]). Precision is the share of
+//! extracted text found in the gold standard, recall is the share of the gold
+//! standard found in extracted text.
+use unicode_normalization::char::is_combining_mark;
+
+/// Extraction quality scores, all in `0.0..=1.0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scores {
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+}
+
+/// Units the longest common subsequence is computed over.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Tokenization {
+    /// Whitespace separated words, a word counts only if it matches fully.
+    Word,
+    /// Single chars, including spaces between words.
+    #[default]
+    Char,
+}
+
+/// Collapses any whitespace sequences into single spaces.
+pub fn normalize_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Removes punctuation (except apostrophes), collapses whitespace and
+/// lowercases the text.
+///
+/// Word chars are about the ones regex `\w` matches (letters, marks, digits
+/// and connectors like `_`), everything else but apostrophes counts as a
+/// word separator, same as whitespace. Unlike `\w`, other numeric chars like
+/// `²` or `½` are kept as well.
+pub fn clean_and_normalize_text(text: &str) -> String {
+    let mut cleaned = String::with_capacity(text.len());
+    let mut separated = false;
+    for c in text.chars() {
+        if is_word_char(c) || c == '\'' {
+            if separated && !cleaned.is_empty() {
+                cleaned.push(' ');
+            }
+            cleaned.push(c);
+            separated = false;
+        } else {
+            separated = true;
+        }
+    }
+    // lowercased as a whole, a final sigma depends on the next char
+    cleaned.to_lowercase()
+}
+
+fn is_word_char(c: char) -> bool {
+    // connector punctuation (`Pc`) and zero width (non-)joiners
+    const CONNECTORS: [char; 12] = [
+        '_', '\u{203f}', '\u{2040}', '\u{2054}', '\u{fe33}', '\u{fe34}',
+        '\u{fe4d}', '\u{fe4e}', '\u{fe4f}', '\u{ff3f}', '\u{200c}', '\u{200d}',
+    ];
+    c.is_alphanumeric() || is_combining_mark(c) || CONNECTORS.contains(&c)
+}
+
+/// Scores extracted text against the gold standard.
+///
+/// Empty texts score `0.0` instead of producing `NaN`.
+///
+/// # Examples
+///
+/// ```no_run
+/// let scores = score_extraction("Hello, world!", "hello world and more");
+/// assert!(scores.recall > scores.precision);
+/// ```
+pub fn score_extraction(gold: &str, extracted: &str) -> Scores {
+    evaluate(extracted, gold, Tokenization::Char)
+}
+
+/// Scores extracted text against the gold standard, comparing them by
+/// `tokenization` units.
+///
+/// Empty texts score `0.0` instead of producing `NaN`.
+///
+/// # Examples
+///
+/// ```no_run
+/// let scores = evaluate(&content, &gold, Tokenization::Word);
+/// assert!(scores.f1 > 0.8);
+/// ```
+pub fn evaluate(extracted: &str, gold: &str, tokenization: Tokenization) -> Scores {
+    let (gold, extracted) = (
+        clean_and_normalize_text(gold),
+        clean_and_normalize_text(extracted),
+    );
+    let (lcs_length, extracted_len, gold_len) = match tokenization {
+        Tokenization::Word => {
+            let gold: Vec<&str> =
+                gold.split(' ').filter(|w| !w.is_empty()).collect();
+            let extracted: Vec<&str> =
+                extracted.split(' ').filter(|w| !w.is_empty()).collect();
+            (
+                calculate_lcs(&gold, &extracted),
+                extracted.len(),
+                gold.len(),
+            )
+        }
+        Tokenization::Char => {
+            let gold: Vec<char> = gold.chars().collect();
+            let extracted: Vec<char> = extracted.chars().collect();
+            (
+                calculate_lcs(&gold, &extracted),
+                extracted.len(),
+                gold.len(),
+            )
+        }
+    };
+
+    let lcs_length = lcs_length as f64;
+    let precision = ratio(lcs_length, extracted_len);
+    let recall = ratio(lcs_length, gold_len);
+    let f1 = if precision + recall > 0.0 {
+        2.0 * (precision * recall) / (precision + recall)
+    } else {
+        0.0
+    };
+
+    Scores {
+        precision,
+        recall,
+        f1,
+    }
+}
+
+/// ROUGE-L scores, all in `0.0..=1.0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RougeScore {
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+}
+
+/// ROUGE-L (Lin, 2004) of `candidate` against `reference`, computed over
+/// the longest common subsequence of their word tokens.
+///
+/// Texts are lowercased and punctuation is dropped before splitting into
+/// words, as in `clean_and_normalize_text`. Unlike `score_extraction`,
+/// partially matching words don't count. F1 is the balanced F-measure
+/// (`beta = 1`). Empty texts score `0.0`.
+///
+/// # Examples
+///
+/// ```no_run
+/// let score = rouge_l("the cat sat on the mat", "the cat is on the mat");
+/// assert!((score.f1 - 5.0 / 6.0).abs() < 1e-9);
+/// ```
+pub fn rouge_l(candidate: &str, reference: &str) -> RougeScore {
+    let candidate = clean_and_normalize_text(candidate);
+    let reference = clean_and_normalize_text(reference);
+    let candidate: Vec<&str> = candidate.split_whitespace().collect();
+    let reference: Vec<&str> = reference.split_whitespace().collect();
+
+    let lcs_length = calculate_lcs(&candidate, &reference) as f64;
+    let precision = ratio(lcs_length, candidate.len());
+    let recall = ratio(lcs_length, reference.len());
+    let f1 = if precision + recall > 0.0 {
+        2.0 * (precision * recall) / (precision + recall)
+    } else {
+        0.0
+    };
+
+    RougeScore {
+        precision,
+        recall,
+        f1,
+    }
+}
+
+fn ratio(value: f64, total: usize) -> f64 {
+    match total {
+        0 => 0.0,
+        _ => value / total as f64,
+    }
+}
+
+/// Length of the longest common subsequence, O(n) memory.
+fn calculate_lcs<T: PartialEq>(s1: &[T], s2: &[T]) -> usize {
+    let (m, n) = (s1.len(), s2.len());
+    let mut prev = vec![0; n + 1];
+    let mut curr = vec![0; n + 1];
+
+    for i in 1..=m {
+        for j in 1..=n {
+            if s1[i - 1] == s2[j - 1] {
+                curr[j] = prev[j - 1] + 1;
+            } else {
+                curr[j] = curr[j - 1].max(prev[j]);
+            }
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[n]
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_and_normalize_text_matches_regex() {
+        let punctuation = regex::Regex::new(r"[^\w\s']").unwrap();
+        let spaces = regex::Regex::new(r"\s+").unwrap();
+        let with_regex = |text: &str| {
+            let text = punctuation.replace_all(text, " ");
+            let text = spaces.replace_all(&text, " ");
+            text.to_lowercase().trim().to_string()
+        };
+        for text in [
+            "",
+            " \t\n ",
+            "...!?",
+            "  Hello,   World! It's\nME. ",
+            "snake_case, x2 and 1/2 — \"quoted\" (parens)",
+            "tie\u{203f}bar\u{ff3f}wide, zero\u{200d}width",
+            "ΟΔΟΣ ΣΟΦΙΑΣ, Привет: мир; 你好，世界",
+            "cafe\u{301}\u{a0}naïve\u{2003}résumé…",
+            "'tis the season's end'",
+        ] {
+            assert_eq!(
+                clean_and_normalize_text(text),
+                with_regex(text),
+                "{text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_clean_and_normalize_text() {
+        assert_eq!(
+            clean_and_normalize_text("  Hello,   World! It's\nME. "),
+            "hello world it's me"
+        );
+        assert_eq!(clean_and_normalize_text("x² and ½"), "x² and ½");
+        assert_eq!(normalize_text(" a \t b\n\nc "), "a b c");
+    }
+
+    #[test]
+    fn test_score_exact_match() {
+        let scores = score_extraction("Hello, world!", "hello world");
+        assert_eq!(scores.precision, 1.0);
+        assert_eq!(scores.recall, 1.0);
+        assert_eq!(scores.f1, 1.0);
+    }
+
+    #[test]
+    fn test_score_partial() {
+        // lcs("abcd", "abxx") = "ab"
+        let scores = score_extraction("abcd", "abxx");
+        assert_eq!(scores.precision, 0.5);
+        assert_eq!(scores.recall, 0.5);
+        assert_eq!(scores.f1, 0.5);
+
+        // extracted "ab" is a subsequence of the gold standard
+        let scores = score_extraction("abcd", "ab");
+        assert_eq!(scores.precision, 1.0);
+        assert_eq!(scores.recall, 0.5);
+        assert!((scores.f1 - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_score_empty() {
+        let scores = score_extraction("gold", "");
+        assert_eq!(scores.precision, 0.0);
+        assert_eq!(scores.recall, 0.0);
+        assert_eq!(scores.f1, 0.0);
+    }
+
+    #[test]
+    fn test_evaluate_words() {
+        // lcs of words is "the cat the mat"
+        let scores = evaluate(
+            "The cat sat on the mat.",
+            "the cat lay under the mat",
+            Tokenization::Word,
+        );
+        assert!((scores.precision - 4.0 / 6.0).abs() < 1e-9);
+        assert!((scores.recall - 4.0 / 6.0).abs() < 1e-9);
+        assert!((scores.f1 - 4.0 / 6.0).abs() < 1e-9);
+
+        // "cat" and "cap" share chars but not words
+        let scores = evaluate("cat", "cap", Tokenization::Word);
+        assert_eq!(scores.f1, 0.0);
+        let scores = evaluate("cat", "cap", Tokenization::Char);
+        assert!((scores.f1 - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_evaluate_order() {
+        // extracted "a b" is a subsequence of gold "a b c d"
+        let scores = evaluate("a b", "a b c d", Tokenization::Word);
+        assert_eq!(scores.precision, 1.0);
+        assert_eq!(scores.recall, 0.5);
+        assert_eq!(
+            score_extraction("abcd", "ab"),
+            evaluate("ab", "abcd", Tokenization::Char)
+        );
+    }
+
+    #[test]
+    fn test_evaluate_empty() {
+        for tokenization in [Tokenization::Word, Tokenization::Char] {
+            let zeros = Scores {
+                precision: 0.0,
+                recall: 0.0,
+                f1: 0.0,
+            };
+            assert_eq!(evaluate("", "gold", tokenization), zeros);
+            assert_eq!(evaluate("text", "", tokenization), zeros);
+            assert_eq!(evaluate("", "", tokenization), zeros);
+            assert_eq!(evaluate("!!!", "...", tokenization), zeros);
+        }
+    }
+
+    #[test]
+    fn test_lcs_words() {
+        let lcs = |a: &str, b: &str| {
+            let a: Vec<&str> = a.split_whitespace().collect();
+            let b: Vec<&str> = b.split_whitespace().collect();
+            calculate_lcs(&a, &b)
+        };
+        assert_eq!(lcs("a b c d", "a b c d"), 4);
+        assert_eq!(lcs("a b c d", "b d"), 2);
+        assert_eq!(lcs("a b c d", "d c b a"), 1);
+        assert_eq!(
+            lcs("police killed the gunman", "the gunman killed police"),
+            2
+        );
+        assert_eq!(lcs("a b c", ""), 0);
+    }
+
+    #[test]
+    fn test_rouge_l() {
+        // lcs is "the cat on the mat", 5 of 6 words on both sides
+        let score = rouge_l("The cat sat on the mat.", "the cat is on the mat");
+        assert!((score.precision - 5.0 / 6.0).abs() < 1e-9);
+        assert!((score.recall - 5.0 / 6.0).abs() < 1e-9);
+        assert!((score.f1 - 5.0 / 6.0).abs() < 1e-9);
+
+        // lcs is "police the", 2 of 4 words on both sides
+        let score = rouge_l("police kill the gunman", "police killed the gunmen");
+        assert_eq!(score.precision, 0.5);
+        assert_eq!(score.recall, 0.5);
+
+        let score = rouge_l("a b", "a b c d e f");
+        assert_eq!(score.precision, 1.0);
+        assert!((score.recall - 2.0 / 6.0).abs() < 1e-9);
+        assert!((score.f1 - 0.5).abs() < 1e-9);
+
+        assert_eq!(rouge_l("", "reference").f1, 0.0);
+        assert_eq!(rouge_l("candidate", "").f1, 0.0);
+    }
+}
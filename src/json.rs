@@ -0,0 +1,262 @@
+//! JSON export and import of a calculated density tree.
+//!
+//! Analysis can be cached this way and the tree restored later without
+//! parsing the HTML and recalculating densities. Nodes are stored as a flat
+//! list in document order, each one with the position of its parent in the
+//! list, so deep documents don't hit the nesting limit of `serde_json`.
+//!
+//! `node_id`s refer to the DOM of the document the tree was built from, they
+//! are stored as indexes of the DOM nodes (in parse order) and mapped back to
+//! nodes of the document passed to `from_json`. That should be the same HTML
+//! parsed again, parsing is deterministic.
+use crate::scraper::Html;
+use crate::{DensityNode, DensityTree, DomExtractionError};
+use ego_tree::{NodeId, Tree};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+impl DensityTree {
+    /// Serializes the tree into JSON, `document` is the one the tree was
+    /// built from.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// let mut dtree = DensityTree::from_document(&document)?;
+    /// dtree.calculate_density_sum()?;
+    /// std::fs::write("tree.json", dtree.to_json(&document)?)?;
+    /// ```
+    pub fn to_json(&self, document: &Html) -> Result<String, DomExtractionError> {
+        // `NodeId` doesn't expose its index, but DOM nodes are iterated in
+        // the order they were inserted, which is the index order
+        let dom_indexes: HashMap<NodeId, usize> = document
+            .tree
+            .nodes()
+            .enumerate()
+            .map(|(index, node)| (node.id(), index))
+            .collect();
+
+        let mut positions: HashMap<NodeId, usize> = HashMap::new();
+        let mut nodes = Vec::new();
+        for (position, node) in self.tree.root().descendants().enumerate() {
+            positions.insert(node.id(), position);
+            let value = node.value();
+            let dom_index = dom_indexes
+                .get(&value.node_id)
+                .ok_or(DomExtractionError::NodeAccessError(value.node_id))?;
+            let parent =
+                node.parent().and_then(|parent| positions.get(&parent.id()));
+            nodes.push(json!({
+                "node_id": dom_index,
+                "parent": parent,
+                "char_count": value.char_count,
+                "tag_count": value.tag_count,
+                "link_char_count": value.link_char_count,
+                "link_tag_count": value.link_tag_count,
+                "density": value.density,
+                "density_sum": value.density_sum,
+            }));
+        }
+        Ok(json!({
+            "nodes": nodes,
+            "rooted_at_element": self.rooted_at_element,
+        })
+        .to_string())
+    }
+
+    /// Restores a tree serialized with `to_json`, including `density` and
+    /// `density_sum`, so nothing has to be recalculated. `document` is the
+    /// one the tree was built from, node indexes outside of it are rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// let document = Html::parse_document(&html);
+    /// let json = std::fs::read_to_string("tree.json")?;
+    /// let dtree = DensityTree::from_json(&json, &document)?;
+    /// let content = dtree.extract_content(&document)?;
+    /// ```
+    pub fn from_json(
+        json: &str,
+        document: &Html,
+    ) -> Result<Self, DomExtractionError> {
+        let value: Value = serde_json::from_str(json)
+            .map_err(|err| DomExtractionError::InvalidJson(err.to_string()))?;
+        let nodes = value
+            .get("nodes")
+            .and_then(Value::as_array)
+            .filter(|nodes| !nodes.is_empty())
+            .ok_or_else(|| invalid("expected a non-empty \"nodes\" array"))?;
+
+        let node_ids = dom_node_ids(nodes, document)?;
+        let mut tree: Option<Tree<DensityNode>> = None;
+        let mut ids: Vec<NodeId> = Vec::with_capacity(nodes.len());
+        for (position, node) in nodes.iter().enumerate() {
+            let node = node
+                .as_object()
+                .ok_or_else(|| invalid("node is not an object"))?;
+            let density_node = parse_node(node, node_ids[position])?;
+            let id = match (tree.as_mut(), node.get("parent")) {
+                (None, Some(Value::Null) | None) => {
+                    let new_tree = Tree::new(density_node);
+                    let id = new_tree.root().id();
+                    tree = Some(new_tree);
+                    id
+                }
+                (Some(tree), Some(parent)) => {
+                    // parents come before their children in document order
+                    let parent_id = parent
+                        .as_u64()
+                        .and_then(|parent| ids.get(parent as usize))
+                        .ok_or_else(|| invalid("parent must precede the node"))?;
+                    let mut parent = tree
+                        .get_mut(*parent_id)
+                        .ok_or(DomExtractionError::NodeAccessError(*parent_id))?;
+                    parent.append(density_node).id()
+                }
+                _ => return Err(invalid("only the first node can be the root")),
+            };
+            ids.push(id);
+        }
+
+        let tree = tree.ok_or_else(|| invalid("no root node"))?;
+        let rooted_at_element = value
+            .get("rooted_at_element")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        Ok(Self {
+            tree,
+            rooted_at_element,
+        })
+    }
+}
+
+fn invalid(message: &str) -> DomExtractionError {
+    DomExtractionError::InvalidJson(message.to_string())
+}
+
+/// Maps DOM node indexes back to `NodeId`s of the document.
+fn dom_node_ids(
+    nodes: &[Value],
+    document: &Html,
+) -> Result<Vec<NodeId>, DomExtractionError> {
+    let dom_ids: Vec<NodeId> =
+        document.tree.nodes().map(|node| node.id()).collect();
+    nodes
+        .iter()
+        .map(|node| {
+            let index =
+                node.get("node_id").and_then(Value::as_u64).ok_or_else(|| {
+                    invalid("\"node_id\" must be a non-negative integer")
+                })?;
+            usize::try_from(index)
+                .ok()
+                .and_then(|index| dom_ids.get(index).copied())
+                .ok_or_else(|| invalid("\"node_id\" is not in the document"))
+        })
+        .collect()
+}
+
+fn parse_node(
+    node: &Map<String, Value>,
+    node_id: NodeId,
+) -> Result<DensityNode, DomExtractionError> {
+    let count = |key: &str| {
+        node.get(key)
+            .and_then(Value::as_u64)
+            .and_then(|count| u32::try_from(count).ok())
+            .ok_or_else(|| {
+                invalid(&format!("\"{key}\" must be a 32-bit unsigned integer"))
+            })
+    };
+    let density = node
+        .get("density")
+        .and_then(Value::as_f64)
+        .ok_or_else(|| invalid("\"density\" must be a number"))?;
+    let density_sum =
+        match node.get("density_sum") {
+            None | Some(Value::Null) => None,
+            Some(value) => Some(value.as_f64().ok_or_else(|| {
+                invalid("\"density_sum\" must be a number or null")
+            })? as f32),
+        };
+
+    Ok(DensityNode {
+        node_id,
+        char_count: count("char_count")?,
+        tag_count: count("tag_count")?,
+        link_char_count: count("link_char_count")?,
+        link_tag_count: count("link_tag_count")?,
+        density: density as f32,
+        density_sum,
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::tests::{build_dom, read_file};
+
+    #[test]
+    fn test_json_round_trip() {
+        let content = read_file("html/test_1.html").unwrap();
+        let document = build_dom(content.as_str());
+        let mut dtree = DensityTree::from_document(&document).unwrap();
+        dtree.calculate_density_sum().unwrap();
+
+        let json = dtree.to_json(&document).unwrap();
+        let restored = DensityTree::from_json(&json, &document).unwrap();
+
+        let fields = |node: &DensityNode| {
+            (
+                node.node_id,
+                node.char_count,
+                node.tag_count,
+                node.link_char_count,
+                node.link_tag_count,
+                node.density,
+                node.density_sum,
+            )
+        };
+        let original: Vec<_> =
+            dtree.sorted_nodes().into_iter().map(fields).collect();
+        let sorted: Vec<_> =
+            restored.sorted_nodes().into_iter().map(fields).collect();
+        assert_eq!(sorted, original);
+        assert_eq!(restored.tree.values().count(), dtree.tree.values().count());
+
+        // node ids still point into the same document
+        assert_eq!(
+            restored.extract_content(&document).unwrap(),
+            dtree.extract_content(&document).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_from_json_invalid() {
+        let document = build_dom("<html><body><p>text</p></body></html>");
+        assert!(matches!(
+            DensityTree::from_json("not json", &document),
+            Err(DomExtractionError::InvalidJson(_))
+        ));
+        assert!(matches!(
+            DensityTree::from_json(r#"{"nodes": []}"#, &document),
+            Err(DomExtractionError::InvalidJson(_))
+        ));
+        let orphan = r#"{"nodes": [{"node_id": 1, "parent": 5, "char_count": 0,
+            "tag_count": 0, "link_char_count": 0, "link_tag_count": 0,
+            "density": 0.0, "density_sum": null}]}"#;
+        assert!(matches!(
+            DensityTree::from_json(orphan, &document),
+            Err(DomExtractionError::InvalidJson(_))
+        ));
+        let out_of_range = r#"{"nodes": [{"node_id": 4000000000, "parent": null,
+            "char_count": 0, "tag_count": 0, "link_char_count": 0,
+            "link_tag_count": 0, "density": 0.0, "density_sum": null}]}"#;
+        assert!(matches!(
+            DensityTree::from_json(out_of_range, &document),
+            Err(DomExtractionError::InvalidJson(_))
+        ));
+    }
+}
@@ -0,0 +1,213 @@
+//! Language detection for extracted content.
+//!
+//! Detection is done with `whatlang`, which scores character trigrams
+//! against per-language profiles. It is cheap enough to run on every
+//! extracted document, but needs a reasonable amount of text to be useful.
+use crate::scraper::Html;
+use crate::{ContentBlock, DensityTree, DomExtractionError};
+use whatlang::Lang;
+
+/// Texts shorter than this (in chars) don't carry enough trigrams
+/// for a meaningful guess.
+const MIN_TEXT_LENGTH: usize = 20;
+
+/// Detects language of the text and returns its ISO 639-1 code
+/// (e.g. `"en"`, `"ru"`, `"ja"`).
+///
+/// Returns `None` when the text is too short or the language can't be
+/// identified.
+///
+/// # Examples
+///
+/// ```no_run
+/// let lang = detect_language("The quick brown fox jumps over the lazy dog");
+/// assert_eq!(lang, Some("en"));
+/// ```
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let text = text.trim();
+    if text.chars().count() < MIN_TEXT_LENGTH {
+        return None;
+    }
+    whatlang::detect_lang(text).map(iso_639_1)
+}
+
+impl DensityTree {
+    /// Extracts the main content and detects its language.
+    ///
+    /// Returns content as `extract_content` does together with the
+    /// ISO 639-1 code of the detected language, if any.
+    pub fn extract_content_with_language(
+        &self,
+        document: &Html,
+    ) -> Result<(String, Option<String>), DomExtractionError> {
+        let content = self.extract_content(document)?;
+        let language = detect_language(&content).map(str::to_string);
+        Ok((content, language))
+    }
+
+    /// Extracts the main content as blocks, as `extract_blocks` does, with
+    /// `ContentBlock::language` of each block set to its detected language.
+    ///
+    /// Useful on multilingual pages, e.g. with translations side by side.
+    /// Blocks too short for detection get `None`.
+    pub fn extract_blocks_with_language(
+        &self,
+        document: &Html,
+    ) -> Result<Vec<ContentBlock>, DomExtractionError> {
+        let mut blocks = self.extract_blocks(document)?;
+        for block in &mut blocks {
+            block.language = detect_language(&block.text).map(str::to_string);
+        }
+        Ok(blocks)
+    }
+}
+
+/// Maps `whatlang` ISO 639-3 languages onto ISO 639-1 codes.
+fn iso_639_1(lang: Lang) -> &'static str {
+    match lang {
+        Lang::Epo => "eo",
+        Lang::Eng => "en",
+        Lang::Rus => "ru",
+        Lang::Cmn => "zh",
+        Lang::Spa => "es",
+        Lang::Por => "pt",
+        Lang::Ita => "it",
+        Lang::Ben => "bn",
+        Lang::Fra => "fr",
+        Lang::Deu => "de",
+        Lang::Ukr => "uk",
+        Lang::Kat => "ka",
+        Lang::Ara => "ar",
+        Lang::Hin => "hi",
+        Lang::Jpn => "ja",
+        Lang::Heb => "he",
+        Lang::Yid => "yi",
+        Lang::Pol => "pl",
+        Lang::Amh => "am",
+        Lang::Jav => "jv",
+        Lang::Kor => "ko",
+        Lang::Nob => "nb",
+        Lang::Dan => "da",
+        Lang::Swe => "sv",
+        Lang::Fin => "fi",
+        Lang::Tur => "tr",
+        Lang::Nld => "nl",
+        Lang::Hun => "hu",
+        Lang::Ces => "cs",
+        Lang::Ell => "el",
+        Lang::Bul => "bg",
+        Lang::Bel => "be",
+        Lang::Mar => "mr",
+        Lang::Kan => "kn",
+        Lang::Ron => "ro",
+        Lang::Slv => "sl",
+        Lang::Hrv => "hr",
+        Lang::Srp => "sr",
+        Lang::Mkd => "mk",
+        Lang::Lit => "lt",
+        Lang::Lav => "lv",
+        Lang::Est => "et",
+        Lang::Tam => "ta",
+        Lang::Vie => "vi",
+        Lang::Urd => "ur",
+        Lang::Tha => "th",
+        Lang::Guj => "gu",
+        Lang::Uzb => "uz",
+        Lang::Pan => "pa",
+        Lang::Aze => "az",
+        Lang::Ind => "id",
+        Lang::Tel => "te",
+        Lang::Pes => "fa",
+        Lang::Mal => "ml",
+        Lang::Ori => "or",
+        Lang::Mya => "my",
+        Lang::Nep => "ne",
+        Lang::Sin => "si",
+        Lang::Khm => "km",
+        Lang::Tuk => "tk",
+        Lang::Aka => "ak",
+        Lang::Zul => "zu",
+        Lang::Sna => "sn",
+        Lang::Afr => "af",
+        Lang::Lat => "la",
+        Lang::Slk => "sk",
+        Lang::Cat => "ca",
+        Lang::Tgl => "tl",
+        Lang::Hye => "hy",
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::tests::{build_dom, read_file};
+
+    #[test]
+    fn test_detect_english() {
+        let text = "Miners are racing to secure lithium deposits as demand \
+                    for batteries keeps growing year over year.";
+        assert_eq!(detect_language(text), Some("en"));
+    }
+
+    #[test]
+    fn test_detect_russian() {
+        let text = "Горнодобывающие компании спешат получить доступ к \
+                    месторождениям лития, так как спрос на батареи растёт.";
+        assert_eq!(detect_language(text), Some("ru"));
+    }
+
+    #[test]
+    fn test_detect_japanese() {
+        let text = "電池の需要が年々高まる中、鉱山会社はリチウムの鉱床を\
+                    確保しようと競い合っています。";
+        assert_eq!(detect_language(text), Some("ja"));
+    }
+
+    #[test]
+    fn test_detect_short_text() {
+        assert_eq!(detect_language(""), None);
+        assert_eq!(detect_language("Hello"), None);
+    }
+
+    #[test]
+    fn test_extract_content_with_language() {
+        let content = read_file("html/article.html").unwrap();
+        let document = build_dom(content.as_str());
+        let mut dtree = DensityTree::from_document(&document).unwrap();
+        dtree.calculate_density_sum().unwrap();
+
+        let (content, language) =
+            dtree.extract_content_with_language(&document).unwrap();
+        assert!(!content.is_empty());
+        assert_eq!(language.as_deref(), Some("en"));
+    }
+
+    #[test]
+    fn test_extract_blocks_with_language() {
+        let content = read_file("html/bilingual.html").unwrap();
+        let document = build_dom(content.as_str());
+        let mut dtree = DensityTree::from_document(&document).unwrap();
+        dtree.calculate_density_sum().unwrap();
+
+        let blocks = dtree.extract_blocks_with_language(&document).unwrap();
+        let languages: Vec<Option<&str>> = blocks
+            .iter()
+            .map(|block| block.language.as_deref())
+            .collect();
+        assert!(languages.contains(&Some("en")));
+        assert!(languages.contains(&Some("fr")));
+        for block in &blocks {
+            if !block.text.contains("compagnies") {
+                assert_eq!(block.language.as_deref(), Some("en"));
+            }
+        }
+
+        // plain extraction doesn't detect languages
+        assert!(dtree
+            .extract_blocks(&document)
+            .unwrap()
+            .iter()
+            .all(|block| block.language.is_none()));
+    }
+}
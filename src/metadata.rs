@@ -0,0 +1,592 @@
+//! Article metadata extraction.
+//!
+//! Title, byline, publish date, OpenGraph fields and JSON-LD blocks usually
+//! live outside of the main content block (in `<head>` or in a header above
+//! the article body), so they are looked up directly in the `scraper::Html`
+//! document instead of the density tree.
+use crate::scraper::{Html, Selector};
+use crate::selectors::{
+    ANCHOR_SELECTOR, AUTHOR_META_SELECTOR, BYLINE_SELECTOR, CANONICAL_SELECTOR,
+    H1_SELECTOR, IMG_SELECTOR, JSON_LD_SELECTOR, NEXT_REL_SELECTOR,
+    OG_IMAGE_SELECTOR, OG_SELECTOR, OG_TITLE_SELECTOR, OG_URL_SELECTOR,
+    PUBLISHED_META_SELECTOR, TIME_SELECTOR, TITLE_SELECTOR,
+};
+use crate::{
+    document_base, resolve_link, unicode, DensityTree, DomExtractionError, Url,
+};
+
+/// schema.org types treated as articles by `extract_json_ld_articles`
+const JSON_LD_ARTICLE_TYPES: [&str; 2] = ["Article", "NewsArticle"];
+
+/// Words of pagination anchors pointing at the next page, matched as whole
+/// words of short anchor texts.
+const NEXT_PAGE_WORDS: [&str; 2] = ["next", "older"];
+
+/// Arrows used as (or next to) the label of a "next page" anchor.
+const NEXT_PAGE_ARROWS: [&str; 4] = ["›", "»", "→", ">"];
+
+/// Anchors with more words (arrows aside) are prose ("the next big thing")
+/// rather than pagination controls like "Next page ›".
+const MAX_NEXT_PAGE_WORDS: usize = 2;
+
+/// Main content of the document together with its metadata.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Article {
+    pub title: Option<String>,
+    pub byline: Option<String>,
+    pub published: Option<String>,
+    pub content: String,
+}
+
+impl Article {
+    /// Estimated reading time of the content, see
+    /// `unicode::reading_time_minutes`.
+    pub fn reading_time_minutes(&self, wpm: u32) -> f32 {
+        unicode::reading_time_minutes(&self.content, wpm)
+    }
+}
+
+/// OpenGraph metadata from `<meta property="og:*">` tags.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OpenGraph {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub url: Option<String>,
+    pub site_name: Option<String>,
+}
+
+/// Image picked by `DensityTree::extract_lead_image`. Width and height are
+/// known only when declared in the markup.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageRef {
+    pub url: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+impl DensityTree {
+    /// Finds the most prominent image of the article, for thumbnails.
+    ///
+    /// Prefers `<meta property="og:image">`, otherwise picks the biggest
+    /// `<img>` of the content container (by `width`/`height` attributes or
+    /// the widest `srcset` candidate), the first one when sizes are unknown.
+    /// Tracking pixels (1x1) and data URIs are skipped. Returned URLs are not
+    /// resolved against the document URL.
+    ///
+    /// As with `extract_content`, `calculate_density_sum` should be called first.
+    pub fn extract_lead_image(&self, document: &Html) -> Option<ImageRef> {
+        if let Some(url) = meta_content(document, &OG_IMAGE_SELECTOR) {
+            return Some(ImageRef {
+                url,
+                width: None,
+                height: None,
+            });
+        }
+
+        let container = self.content_element(document).ok()??;
+        let mut lead: Option<(u64, ImageRef)> = None;
+        for img in container.select(&IMG_SELECTOR) {
+            let Some(image) = image_ref(img.value()) else {
+                continue;
+            };
+            let area = match (image.width, image.height) {
+                (Some(width), Some(height)) => width as u64 * height as u64,
+                (Some(side), None) | (None, Some(side)) => {
+                    side as u64 * side as u64
+                }
+                (None, None) => 0,
+            };
+            // strictly bigger, so the first image wins ties
+            if lead.as_ref().is_none_or(|(max_area, _)| area > *max_area) {
+                lead = Some((area, image));
+            }
+        }
+        lead.map(|(_, image)| image)
+    }
+
+    /// Extracts the main content along with the article title, byline and
+    /// publish date.
+    ///
+    /// Title is taken from `og:title`, then `<title>`, then the first `<h1>`.
+    /// Byline is taken from `<meta name="author">`, then common author markup
+    /// (`rel="author"`, `.byline`, `.author`). Publish date prefers
+    /// `<meta property="article:published_time">` and falls back to the first
+    /// `<time datetime>`.
+    ///
+    /// As with `extract_content`, `calculate_density_sum` should be called first.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// let mut dtree = DensityTree::from_document(&document).unwrap();
+    /// dtree.calculate_density_sum().unwrap();
+    /// let article = dtree.extract_article(&document).unwrap();
+    /// println!("{:?} by {:?}", article.title, article.byline);
+    /// ```
+    pub fn extract_article(
+        &self,
+        document: &Html,
+    ) -> Result<Article, DomExtractionError> {
+        let title = meta_content(document, &OG_TITLE_SELECTOR)
+            .or_else(|| element_text(document, &TITLE_SELECTOR))
+            .or_else(|| element_text(document, &H1_SELECTOR));
+        let byline = meta_content(document, &AUTHOR_META_SELECTOR)
+            .or_else(|| element_text(document, &BYLINE_SELECTOR));
+        let published = meta_content(document, &PUBLISHED_META_SELECTOR)
+            .or_else(|| attr_value(document, &TIME_SELECTOR, "datetime"));
+
+        Ok(Article {
+            title,
+            byline,
+            published,
+            content: self.extract_content(document)?,
+        })
+    }
+}
+
+/// Reads OpenGraph fields from `<meta property="og:*">` tags of the document.
+///
+/// This doesn't depend on density analysis. When a property is repeated
+/// (common for `og:image`), the first non-empty value wins.
+///
+/// # Examples
+///
+/// ```no_run
+/// let document = Html::parse_document(&html_string);
+/// let og = extract_opengraph(&document);
+/// println!("{:?}", og.image);
+/// ```
+pub fn extract_opengraph(document: &Html) -> OpenGraph {
+    let mut og = OpenGraph::default();
+    for elem in document.select(&OG_SELECTOR) {
+        let (Some(property), Some(content)) =
+            (elem.value().attr("property"), elem.value().attr("content"))
+        else {
+            continue;
+        };
+        let content = content.trim();
+        if content.is_empty() {
+            continue;
+        }
+        let field = match property.trim() {
+            "og:title" => &mut og.title,
+            "og:description" => &mut og.description,
+            "og:image" => &mut og.image,
+            "og:url" => &mut og.url,
+            "og:site_name" => &mut og.site_name,
+            _ => continue,
+        };
+        if field.is_none() {
+            *field = Some(content.to_string());
+        }
+    }
+    og
+}
+
+/// Returns the canonical URL of the page from `<link rel="canonical">`,
+/// falling back to `og:url`.
+///
+/// The value is returned as written in the document (trimmed), relative
+/// URLs are not resolved. Like `extract_opengraph`, this doesn't depend on
+/// density analysis.
+///
+/// # Examples
+///
+/// ```no_run
+/// let document = Html::parse_document(&html_string);
+/// if let Some(canonical) = extract_canonical(&document) {
+///     println!("canonical: {canonical}");
+/// }
+/// ```
+pub fn extract_canonical(document: &Html) -> Option<String> {
+    attr_value(document, &CANONICAL_SELECTOR, "href")
+        .or_else(|| meta_content(document, &OG_URL_SELECTOR))
+}
+
+/// Finds the link to the next page of a multi-page article, resolved
+/// against `<base href>` or `base`.
+///
+/// `rel="next"` (on `<link>` or `<a>`) is the explicit signal and wins.
+/// Otherwise the first anchor that looks like a pagination control is taken:
+/// a short text with "next" or "older" as a word or made of arrows like `›`,
+/// or a class mentioning "next".
+///
+/// # Examples
+///
+/// ```no_run
+/// let base = Url::parse("https://example.com/articles/lithium-rush")?;
+/// if let Some(next) = extract_next_page_link(&document, &base) {
+///     println!("continues at {next}");
+/// }
+/// ```
+pub fn extract_next_page_link(document: &Html, base: &Url) -> Option<Url> {
+    let base = document_base(document, base);
+    let resolve = |elem: scraper::ElementRef| {
+        elem.value()
+            .attr("href")
+            .and_then(|href| resolve_link(&base, href))
+    };
+    document
+        .select(&NEXT_REL_SELECTOR)
+        .find_map(resolve)
+        .or_else(|| {
+            document
+                .select(&ANCHOR_SELECTOR)
+                .filter(|anchor| is_next_page_anchor(*anchor))
+                .find_map(resolve)
+        })
+}
+
+fn is_next_page_anchor(anchor: scraper::ElementRef) -> bool {
+    let has_next_class = anchor
+        .value()
+        .classes()
+        .any(|class| class.to_ascii_lowercase().contains("next"));
+    if has_next_class {
+        return true;
+    }
+
+    let text = anchor.text().collect::<String>().to_lowercase();
+    let (arrows, words): (Vec<&str>, Vec<&str>) = text
+        .split_whitespace()
+        .partition(|word| NEXT_PAGE_ARROWS.contains(word));
+    if words.len() > MAX_NEXT_PAGE_WORDS {
+        return false;
+    }
+    if words.is_empty() {
+        return !arrows.is_empty();
+    }
+    words.iter().any(|word| {
+        let word = word.trim_matches(|c: char| !c.is_alphanumeric());
+        NEXT_PAGE_WORDS.contains(&word)
+    })
+}
+
+/// Collects and parses every `<script type="application/ld+json">` block
+/// of the document.
+///
+/// Blocks that fail to parse as JSON are skipped, publishers ship broken
+/// JSON-LD often enough that it shouldn't fail the whole page.
+pub fn extract_json_ld(document: &Html) -> Vec<serde_json::Value> {
+    document
+        .select(&JSON_LD_SELECTOR)
+        .filter_map(|script| {
+            let json = script.text().collect::<String>();
+            serde_json::from_str(json.trim()).ok()
+        })
+        .collect()
+}
+
+/// Returns JSON-LD objects whose `@type` is `Article` or `NewsArticle`.
+///
+/// Top-level arrays and `@graph` containers are flattened, `@type` may be
+/// either a string or an array of strings.
+pub fn extract_json_ld_articles(document: &Html) -> Vec<serde_json::Value> {
+    let mut objects = Vec::new();
+    for value in extract_json_ld(document) {
+        flatten_json_ld(value, &mut objects);
+    }
+    objects.retain(is_json_ld_article);
+    objects
+}
+
+fn flatten_json_ld(value: serde_json::Value, out: &mut Vec<serde_json::Value>) {
+    match value {
+        serde_json::Value::Array(items) => {
+            for item in items {
+                flatten_json_ld(item, out);
+            }
+        }
+        serde_json::Value::Object(mut object) => {
+            if let Some(graph) = object.remove("@graph") {
+                flatten_json_ld(graph, out);
+            }
+            out.push(serde_json::Value::Object(object));
+        }
+        _ => {}
+    }
+}
+
+fn is_json_ld_article(value: &serde_json::Value) -> bool {
+    match value.get("@type") {
+        Some(serde_json::Value::String(kind)) => {
+            JSON_LD_ARTICLE_TYPES.contains(&kind.as_str())
+        }
+        Some(serde_json::Value::Array(kinds)) => kinds
+            .iter()
+            .filter_map(|kind| kind.as_str())
+            .any(|kind| JSON_LD_ARTICLE_TYPES.contains(&kind)),
+        _ => false,
+    }
+}
+
+/// Builds `ImageRef` from `<img>`, taking the widest `srcset` candidate when
+/// present. Returns `None` for data URIs and tracking pixels.
+fn image_ref(img: &scraper::node::Element) -> Option<ImageRef> {
+    let mut width = img.attr("width").and_then(parse_dimension);
+    let height = img.attr("height").and_then(parse_dimension);
+    if width.is_some_and(|w| w <= 1) || height.is_some_and(|h| h <= 1) {
+        return None;
+    }
+
+    let mut url = img.attr("src").map(str::trim).unwrap_or_default();
+    let widest = img.attr("srcset").and_then(|srcset| {
+        srcset
+            .split(',')
+            .filter_map(|candidate| {
+                let mut parts = candidate.split_whitespace();
+                let url = parts.next()?;
+                let width = parts
+                    .next()
+                    .and_then(|descriptor| descriptor.strip_suffix('w'))
+                    .and_then(|w| w.parse::<u32>().ok());
+                Some((url, width))
+            })
+            .max_by_key(|(_, width)| *width)
+    });
+    if let Some((srcset_url, srcset_width)) = widest {
+        if srcset_width.is_some() || url.is_empty() || url.starts_with("data:") {
+            url = srcset_url;
+            width = srcset_width.or(width);
+        }
+    }
+
+    if url.is_empty() || url.starts_with("data:") {
+        return None;
+    }
+    Some(ImageRef {
+        url: url.to_string(),
+        width,
+        height,
+    })
+}
+
+/// Parses `width`/`height` attribute values like `"640"` or `"640px"`.
+fn parse_dimension(value: &str) -> Option<u32> {
+    value.trim().trim_end_matches("px").parse().ok()
+}
+
+/// Returns trimmed `content` attribute of the first matching `<meta>` tag.
+fn meta_content(document: &Html, selector: &Selector) -> Option<String> {
+    attr_value(document, selector, "content")
+}
+
+/// Returns trimmed value of the attribute of the first matching element,
+/// empty values are skipped.
+fn attr_value(document: &Html, selector: &Selector, attr: &str) -> Option<String> {
+    document
+        .select(selector)
+        .filter_map(|elem| elem.value().attr(attr))
+        .map(str::trim)
+        .find(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// Returns whitespace-normalized text of the first matching element
+/// with non-empty text.
+fn element_text(document: &Html, selector: &Selector) -> Option<String> {
+    document
+        .select(selector)
+        .map(|elem| elem.text().collect::<Vec<_>>().join(" "))
+        .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+        .find(|text| !text.is_empty())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::tests::{build_dom, read_file};
+
+    #[test]
+    fn test_extract_article() {
+        let content = read_file("html/article.html").unwrap();
+        let document = build_dom(content.as_str());
+        let mut dtree = DensityTree::from_document(&document).unwrap();
+        dtree.calculate_density_sum().unwrap();
+
+        let article = dtree.extract_article(&document).unwrap();
+        assert_eq!(
+            article.title.as_deref(),
+            Some("Lithium rush | Example News")
+        );
+        assert_eq!(article.byline.as_deref(), Some("Jane Doe"));
+        assert_eq!(article.published.as_deref(), Some("2024-03-15T08:30:00Z"));
+        assert!(article.content.contains("lithium deposits"));
+        assert!(article.reading_time_minutes(200) > 0.0);
+    }
+
+    #[test]
+    fn test_extract_lead_image() {
+        let content = read_file("html/lead_image.html").unwrap();
+        let document = build_dom(content.as_str());
+        let mut dtree = DensityTree::from_document(&document).unwrap();
+        dtree.calculate_density_sum().unwrap();
+
+        // no og:image, tracking pixel and data URI are skipped, the logo is
+        // outside of the content
+        let image = dtree.extract_lead_image(&document).unwrap();
+        assert_eq!(image.url, "/images/mine-large.jpg");
+        assert_eq!(image.width, Some(1280));
+    }
+
+    #[test]
+    fn test_extract_lead_image_prefers_opengraph() {
+        let content = read_file("html/opengraph.html").unwrap();
+        let document = build_dom(content.as_str());
+        let mut dtree = DensityTree::from_document(&document).unwrap();
+        dtree.calculate_density_sum().unwrap();
+
+        let image = dtree.extract_lead_image(&document).unwrap();
+        assert_eq!(image.url, "https://example.com/images/mine.jpg");
+    }
+
+    #[test]
+    fn test_extract_opengraph() {
+        let content = read_file("html/opengraph.html").unwrap();
+        let document = build_dom(content.as_str());
+
+        let og = extract_opengraph(&document);
+        assert_eq!(og.title.as_deref(), Some("Lithium rush"));
+        assert_eq!(
+            og.image.as_deref(),
+            Some("https://example.com/images/mine.jpg")
+        );
+        assert_eq!(og.site_name.as_deref(), Some("Example News"));
+        assert_eq!(
+            og.url.as_deref(),
+            Some("https://example.com/news/lithium-rush")
+        );
+        assert!(og.description.is_some());
+    }
+
+    #[test]
+    fn test_extract_opengraph_missing() {
+        let document = build_dom("<html><body><p>No meta</p></body></html>");
+        assert_eq!(extract_opengraph(&document), OpenGraph::default());
+    }
+
+    #[test]
+    fn test_extract_canonical() {
+        let content = read_file("html/paginated.html").unwrap();
+        let document = build_dom(content.as_str());
+        assert_eq!(
+            extract_canonical(&document).as_deref(),
+            Some("https://example.com/articles/lithium-rush")
+        );
+
+        // falls back to og:url
+        let content = read_file("html/opengraph.html").unwrap();
+        let document = build_dom(content.as_str());
+        assert_eq!(
+            extract_canonical(&document).as_deref(),
+            Some("https://example.com/news/lithium-rush")
+        );
+    }
+
+    #[test]
+    fn test_extract_canonical_missing() {
+        let content = read_file("html/article.html").unwrap();
+        let document = build_dom(content.as_str());
+        assert_eq!(extract_canonical(&document), None);
+    }
+
+    #[test]
+    fn test_extract_json_ld_skips_malformed() {
+        let content = read_file("html/json_ld.html").unwrap();
+        let document = build_dom(content.as_str());
+
+        let blocks = extract_json_ld(&document);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0]["headline"], "Lithium rush");
+    }
+
+    #[test]
+    fn test_extract_json_ld_articles() {
+        let document = build_dom(
+            r#"<html><head>
+            <script type="application/ld+json">
+                {"@graph": [
+                    {"@type": "WebSite", "name": "Example"},
+                    {"@type": ["Article"], "headline": "First"}
+                ]}
+            </script>
+            <script type="application/ld+json">
+                [{"@type": "NewsArticle", "headline": "Second"}]
+            </script>
+            </head><body></body></html>"#,
+        );
+
+        let articles = extract_json_ld_articles(&document);
+        let headlines: Vec<_> = articles
+            .iter()
+            .map(|article| article["headline"].as_str().unwrap())
+            .collect();
+        assert_eq!(headlines, vec!["First", "Second"]);
+    }
+
+    #[test]
+    fn test_extract_article_fallbacks() {
+        let document = build_dom(
+            r#"<html><body>
+                <h1>Headline</h1>
+                <span class="byline">John Smith</span>
+                <time datetime="2023-01-02">Jan 2</time>
+                <p>Some text of the article.</p>
+            </body></html>"#,
+        );
+        let mut dtree = DensityTree::from_document(&document).unwrap();
+        dtree.calculate_density_sum().unwrap();
+
+        let article = dtree.extract_article(&document).unwrap();
+        assert_eq!(article.title.as_deref(), Some("Headline"));
+        assert_eq!(article.byline.as_deref(), Some("John Smith"));
+        assert_eq!(article.published.as_deref(), Some("2023-01-02"));
+    }
+
+    #[test]
+    fn test_extract_next_page_link() {
+        let content = read_file("html/paginated.html").unwrap();
+        let document = build_dom(content.as_str());
+        let base = Url::parse("https://example.com/articles/lithium-rush").unwrap();
+
+        // `rel="next"` wins over the "Next ›" anchor
+        assert_eq!(
+            extract_next_page_link(&document, &base).unwrap().as_str(),
+            "https://example.com/articles/lithium-rush/page/2"
+        );
+    }
+
+    #[test]
+    fn test_extract_next_page_link_anchors() {
+        let base = Url::parse("https://example.com/blog/").unwrap();
+        let next = |html: &str| {
+            extract_next_page_link(&build_dom(html), &base).map(String::from)
+        };
+
+        assert_eq!(
+            next(
+                r#"<p><a href="/a">the next big thing</a></p>
+                    <a href="page/2">Older posts »</a>"#
+            ),
+            Some("https://example.com/blog/page/2".to_string())
+        );
+        assert_eq!(
+            next(r#"<a href="?p=1">‹</a><a href="?p=3">›</a>"#),
+            Some("https://example.com/blog/?p=3".to_string())
+        );
+        assert_eq!(
+            next(r#"<a class="nav-next" href="/blog/2"><span>2</span></a>"#),
+            Some("https://example.com/blog/2".to_string())
+        );
+        assert_eq!(
+            next(
+                r##"<base href="https://cdn.example.org/"><a href="#">Next</a>
+                     <a href="p/2">Next page</a>"##
+            ),
+            Some("https://cdn.example.org/p/2".to_string())
+        );
+        assert_eq!(next(r#"<a href="/about">About us</a>"#), None);
+    }
+}
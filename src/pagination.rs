@@ -0,0 +1,132 @@
+//! Content of articles split over several pages.
+//!
+//! Each page of a multi-page article usually repeats the same standfirst,
+//! navigation or "part of a series" box. Pages are extracted one by one and
+//! paragraphs already taken from an earlier page are dropped, so such
+//! boilerplate makes it into the merged content only once.
+use crate::scraper::Html;
+use crate::{
+    get_node_by_id, unicode, write_subtree_text, DensityNode, DensityTree,
+    DomExtractionError,
+};
+use ego_tree::iter::Edge;
+use ego_tree::NodeRef;
+use rustc_hash::FxHashSet;
+
+/// Extracts the main content of every page and concatenates it in the order
+/// of `pages`.
+///
+/// Content is compared across pages paragraph by paragraph (elements with
+/// text of their own, like `<p>` or `<li>`) after `unicode::normalize_text`,
+/// paragraphs seen before are skipped. Links to the following pages can be
+/// found with `extract_next_page_link`.
+///
+/// Fails with the error of the first page which can't be extracted, e.g.
+/// `NoContent` for a page without any text.
+///
+/// # Examples
+///
+/// ```no_run
+/// let pages: Vec<Html> = bodies.iter().map(|b| Html::parse_document(b)).collect();
+/// let content = extract_paginated(&pages)?;
+/// ```
+pub fn extract_paginated(pages: &[Html]) -> Result<String, DomExtractionError> {
+    let mut content = String::new();
+    let mut seen_text = FxHashSet::default();
+    for document in pages {
+        let mut dtree = DensityTree::from_document(document)?;
+        dtree.calculate_density_sum()?;
+        dtree.ensure_text()?;
+        for node in dtree.content_nodes() {
+            for paragraph in paragraphs(node, document)? {
+                if seen_text.insert(unicode::normalize_text(&paragraph)) {
+                    content.push_str(&paragraph);
+                    content.push(' ');
+                }
+            }
+        }
+    }
+    Ok(content.trim().to_string())
+}
+
+/// Splits text of the content node into paragraphs, texts of the topmost
+/// nodes of its subtree which have non-blank text nodes as children.
+fn paragraphs(
+    node: NodeRef<DensityNode>,
+    document: &Html,
+) -> Result<Vec<String>, DomExtractionError> {
+    let mut paragraphs = Vec::new();
+    let mut descendants = node.traverse();
+    while let Some(edge) = descendants.next() {
+        let Edge::Open(node) = edge else { continue };
+        if node.has_children() && !has_own_text(node, document)? {
+            continue;
+        }
+        let mut text = String::new();
+        write_subtree_text(node, document, &mut text)?;
+        if !text.is_empty() {
+            paragraphs.push(text);
+        }
+        for edge in descendants.by_ref() {
+            if matches!(edge, Edge::Close(closed) if closed.id() == node.id()) {
+                break;
+            }
+        }
+    }
+    Ok(paragraphs)
+}
+
+fn has_own_text(
+    node: NodeRef<DensityNode>,
+    document: &Html,
+) -> Result<bool, DomExtractionError> {
+    for child in node.children().filter(|child| !child.has_children()) {
+        let dom_node = get_node_by_id(child.value().node_id, document)?;
+        if dom_node
+            .value()
+            .as_text()
+            .is_some_and(|text| !text.trim().is_empty())
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::tests::{build_dom, read_file};
+
+    #[test]
+    fn test_extract_paginated() {
+        let pages: Vec<Html> = ["html/multipage_1.html", "html/multipage_2.html"]
+            .iter()
+            .map(|path| build_dom(&read_file(path).unwrap()))
+            .collect();
+
+        let content = extract_paginated(&pages).unwrap();
+        assert_eq!(content.matches("five years away").count(), 1);
+        assert!(content.contains("pilot line outside Nagoya"));
+        assert!(content.contains("startup in Colorado"));
+        // page order is kept
+        assert!(
+            content.find("Nagoya").unwrap() < content.find("Colorado").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_extract_paginated_single_page() {
+        let content = read_file("html/multipage_1.html").unwrap();
+        let document = build_dom(content.as_str());
+        let mut dtree = DensityTree::from_document(&document).unwrap();
+        dtree.calculate_density_sum().unwrap();
+
+        let paginated = extract_paginated(std::slice::from_ref(&document)).unwrap();
+        assert_eq!(
+            unicode::normalize_text(&paginated),
+            unicode::normalize_text(&dtree.extract_content(&document).unwrap())
+        );
+    }
+}
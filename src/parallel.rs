@@ -0,0 +1,198 @@
+//! Parallel construction of the density tree.
+//!
+//! `scraper::Html` isn't `Sync`, so the DOM can't be walked from several
+//! threads at once. Instead the relevant parts of the DOM are copied into a
+//! lightweight snapshot first (sequentially), then metrics of sibling
+//! subtrees are computed concurrently and merged into their parents exactly
+//! like the sequential `build_density_tree` does. Each node is merged into its
+//! parent exactly once and metrics are integer sums, so the result doesn't
+//! depend on thread scheduling.
+//!
+//! Like `build_density_tree`, nothing here recurses over the DOM, so deeply
+//! nested markup can't overflow the (small) stacks of `rayon` threads.
+use crate::scraper::Html;
+use crate::DomExtractionError;
+use crate::{
+    has_anchor_parent, is_kept_node, BuildOptions, DensityNode, DensityTree,
+};
+use ego_tree::{NodeId, NodeRef, Tree};
+use rayon::prelude::*;
+
+/// Thread-safe copy of a DOM node relevant for density analysis, snapshot
+/// nodes are stored in pre-order.
+struct SnapshotNode {
+    node_id: NodeId,
+    /// `DensityNode` with only the metrics of the DOM node itself
+    own: DensityNode,
+    in_anchor: bool,
+    /// Index of the parent node, `None` for the root
+    parent: Option<usize>,
+    /// Index right after the last node of the subtree
+    end: usize,
+}
+
+impl DensityTree {
+    /// Creates and calculates a `DensityTree`, building sibling subtrees
+    /// concurrently with `rayon`.
+    ///
+    /// The resulting tree (structure, metrics and densities) is identical to
+    /// the one produced by `from_document`. Since the DOM has to be copied into
+    /// a thread-safe snapshot first, this only pays off on large documents.
+    pub fn from_document_parallel(
+        document: &Html,
+    ) -> Result<Self, DomExtractionError> {
+        let body_node = Self::find_body(document)?;
+        let nodes = snapshot(body_node);
+        let mut metrics: Vec<DensityNode> = nodes
+            .iter()
+            .map(|node| DensityNode::new(node.node_id))
+            .collect();
+
+        // go down while nodes have a single child, siblings below are the
+        // subtrees built concurrently
+        let mut split = 0;
+        while split + 1 < nodes.len() && nodes[split + 1].end == nodes[split].end {
+            split += 1;
+        }
+        let (chain, mut rest) = metrics.split_at_mut(split + 1);
+        let mut subtrees = Vec::new();
+        let mut start = split + 1;
+        while start < nodes.len() {
+            let (subtree, tail) = rest.split_at_mut(nodes[start].end - start);
+            subtrees.push((start, subtree));
+            rest = tail;
+            start = nodes[start].end;
+        }
+        subtrees
+            .par_iter_mut()
+            .for_each(|(offset, subtree)| fold(&nodes, subtree, *offset));
+        for (offset, subtree) in &subtrees {
+            chain[split].merge_child_metrics(&subtree[0], nodes[*offset].in_anchor);
+        }
+        fold(&nodes, chain, 0);
+
+        let mut metrics = metrics.into_iter();
+        let Some(root) = metrics.next() else {
+            return Err(DomExtractionError::NoBodyElement);
+        };
+        let mut tree = Tree::new(root);
+        let mut ids = vec![tree.root().id()];
+        for (node, density_node) in nodes[1..].iter().zip(metrics) {
+            let parent_id = ids[node.parent.unwrap_or(0)];
+            let Some(mut parent) = tree.get_mut(parent_id) else {
+                return Err(DomExtractionError::NodeAccessError(node.node_id));
+            };
+            ids.push(parent.append(density_node).id());
+        }
+
+        let mut density_tree = Self {
+            tree,
+            rooted_at_element: false,
+        };
+        density_tree.calculate_density_tree();
+        Ok(density_tree)
+    }
+}
+
+/// Copies the DOM subtree of `root` in pre-order.
+fn snapshot(root: NodeRef<scraper::node::Node>) -> Vec<SnapshotNode> {
+    let build_options = BuildOptions::default();
+    let mut nodes: Vec<SnapshotNode> = Vec::new();
+    let mut stack = vec![(root, None)];
+    while let Some((node, parent)) = stack.pop() {
+        let mut own = DensityNode::new(node.id());
+        own.add_node_metrics(node.value(), &build_options);
+        let index = nodes.len();
+        nodes.push(SnapshotNode {
+            node_id: node.id(),
+            own,
+            in_anchor: has_anchor_parent(node),
+            parent,
+            end: index + 1,
+        });
+        // reversed, so children are popped in document order
+        for child in node.children().rev() {
+            if is_kept_node(child, &build_options) {
+                stack.push((child, Some(index)));
+            }
+        }
+    }
+    for index in (1..nodes.len()).rev() {
+        if let Some(parent) = nodes[index].parent {
+            nodes[parent].end = nodes[parent].end.max(nodes[index].end);
+        }
+    }
+    nodes
+}
+
+/// Calculates metrics of nodes starting at `offset`, children (which come
+/// later in pre-order) are merged before the node's own metrics are added,
+/// same as in `build_density_tree`. Nodes whose parent is out of `metrics`
+/// are left for the caller to merge.
+fn fold(nodes: &[SnapshotNode], metrics: &mut [DensityNode], offset: usize) {
+    for index in (0..metrics.len()).rev() {
+        let snapshot = &nodes[offset + index];
+        let node = &mut metrics[index];
+        node.char_count += snapshot.own.char_count;
+        node.tag_count += snapshot.own.tag_count;
+        node.link_tag_count += snapshot.own.link_tag_count;
+        if node.tag_count > 0 {
+            node.density = node.char_count as f32 / node.tag_count as f32;
+        }
+        if let Some(parent) = snapshot.parent.filter(|parent| *parent >= offset) {
+            // parents come before their children in pre-order
+            let (head, tail) = metrics.split_at_mut(index);
+            head[parent - offset].merge_child_metrics(&tail[0], snapshot.in_anchor);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::tests::build_dom;
+    use std::io::Read;
+
+    fn read_file_from_zip(zip_path: &str, file_name: &str) -> String {
+        let zipfile = std::fs::File::open(zip_path).unwrap();
+        let mut archive = zip::ZipArchive::new(zipfile).unwrap();
+        let mut content = String::new();
+        archive
+            .by_name(file_name)
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        content
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let content = read_file_from_zip(
+            "html/pages.zip",
+            "pages/sas-bankruptcy-protection.html",
+        );
+        let document = build_dom(content.as_str());
+
+        let sequential = DensityTree::from_document(&document).unwrap();
+        let parallel = DensityTree::from_document_parallel(&document).unwrap();
+
+        assert_eq!(format!("{:?}", sequential), format!("{:?}", parallel));
+        assert_eq!(
+            sequential.tree.values().count(),
+            parallel.tree.values().count()
+        );
+        for (a, b) in sequential.tree.nodes().zip(parallel.tree.nodes()) {
+            assert_eq!(a.value().node_id, b.value().node_id);
+            assert_eq!(
+                a.parent().map(|p| p.value().node_id),
+                b.parent().map(|p| p.value().node_id)
+            );
+            assert_eq!(a.value().char_count, b.value().char_count);
+            assert_eq!(a.value().tag_count, b.value().tag_count);
+            assert_eq!(a.value().link_char_count, b.value().link_char_count);
+            assert_eq!(a.value().link_tag_count, b.value().link_tag_count);
+            assert_eq!(a.value().density, b.value().density);
+        }
+    }
+}
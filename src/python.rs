@@ -0,0 +1,110 @@
+//! Python bindings, enabled with the `python` feature.
+//!
+//! Build the extension module with `maturin`, features and the `cdylib`
+//! crate type are set in `pyproject.toml`, so the library itself stays an
+//! rlib for Rust users:
+//!
+//! ```bash
+//! maturin build --release
+//! ```
+//!
+//! ```python
+//! import dom_content_extraction as dce
+//! text = dce.extract_content(html)
+//! ```
+use crate::scraper::Html;
+use crate::{DensityTree, DomExtractionError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+pyo3::create_exception!(
+    dom_content_extraction,
+    DomExtractionException,
+    pyo3::exceptions::PyException
+);
+
+impl From<DomExtractionError> for PyErr {
+    fn from(err: DomExtractionError) -> Self {
+        DomExtractionException::new_err(err.to_string())
+    }
+}
+
+/// Parses the document and builds density tree with density sums.
+fn analyze(html: &str) -> Result<(DensityTree, Html), DomExtractionError> {
+    let document = Html::parse_document(html);
+    let mut dtree = DensityTree::from_document(&document)?;
+    dtree.calculate_density_sum()?;
+    Ok((dtree, document))
+}
+
+/// Extracts the main content of the HTML document as plain text.
+#[pyfunction]
+fn extract_content(html: &str) -> PyResult<String> {
+    let (dtree, document) = analyze(html)?;
+    Ok(dtree.extract_content(&document)?)
+}
+
+/// Extracts the main content as a list of blocks, each one is a dict with
+/// `text`, `density` and `density_sum` keys.
+#[pyfunction]
+fn extract_blocks<'py>(
+    py: Python<'py>,
+    html: &str,
+) -> PyResult<Vec<Bound<'py, PyDict>>> {
+    let (dtree, document) = analyze(html)?;
+    dtree
+        .extract_blocks(&document)?
+        .into_iter()
+        .map(|block| {
+            let dict = PyDict::new(py);
+            dict.set_item("text", block.text)?;
+            dict.set_item("density", block.density)?;
+            dict.set_item("density_sum", block.density_sum)?;
+            Ok(dict)
+        })
+        .collect()
+}
+
+#[pymodule]
+fn dom_content_extraction(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(extract_content, m)?)?;
+    m.add_function(wrap_pyfunction!(extract_blocks, m)?)?;
+    m.add(
+        "DomExtractionException",
+        m.py().get_type::<DomExtractionException>(),
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    const HTML: &str = "<html><body>
+        <nav><a href='/'>Home</a> <a href='/about'>About</a></nav>
+        <article>
+            <p>First paragraph of the article, long enough to be content.</p>
+            <p>Second paragraph of the article, also quite long text.</p>
+        </article>
+    </body></html>";
+
+    #[test]
+    fn test_python_wrappers() {
+        Python::initialize();
+        Python::attach(|py| {
+            let content = extract_content(HTML).unwrap();
+            assert!(content.contains("First paragraph"));
+
+            let blocks = extract_blocks(py, HTML).unwrap();
+            assert!(!blocks.is_empty());
+            let text: String = blocks[0]
+                .get_item("text")
+                .unwrap()
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert!(!text.is_empty());
+        });
+    }
+}
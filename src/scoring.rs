@@ -0,0 +1,74 @@
+//! Density scoring strategies.
+//!
+//! `DensityTree::calculate_density_tree` uses the composite text density from
+//! the CETD paper. Other formulas can be plugged in through `DensityScorer`
+//! and `DensityTree::calculate_density_tree_with`, without touching the rest
+//! of the pipeline (density sums, content selection, extraction).
+use crate::{DensityTree, NodeMetrics};
+
+/// Computes the density of a node from its metrics and the metrics of the
+/// root (`<body>`) node of the tree.
+pub trait DensityScorer {
+    fn score(&self, node: &NodeMetrics, body: &NodeMetrics) -> f32;
+}
+
+/// Composite text density, see `DensityTree::composite_text_density`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CompositeScorer;
+
+impl DensityScorer for CompositeScorer {
+    fn score(&self, node: &NodeMetrics, body: &NodeMetrics) -> f32 {
+        DensityTree::composite_text_density(
+            node.char_count,
+            node.tag_count,
+            node.link_char_count,
+            node.link_tag_count,
+            body.char_count,
+            body.link_char_count,
+        )
+    }
+}
+
+/// Plain chars per tag ratio, see `NodeMetrics::calculate_simple_density`.
+/// Links are ignored.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SimpleScorer;
+
+impl DensityScorer for SimpleScorer {
+    fn score(&self, node: &NodeMetrics, _body: &NodeMetrics) -> f32 {
+        node.calculate_simple_density()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::tests::{build_dom, read_file};
+
+    #[test]
+    fn test_simple_scorer() {
+        let content = read_file("html/test_1.html").unwrap();
+        let document = build_dom(content.as_str());
+        let mut dtree = DensityTree::from_document(&document).unwrap();
+
+        dtree.calculate_density_tree_with(&SimpleScorer);
+        for node in dtree.tree.values() {
+            assert_eq!(node.density, node.metrics().calculate_simple_density());
+        }
+    }
+
+    #[test]
+    fn test_composite_scorer_is_default() {
+        let content = read_file("html/test_1.html").unwrap();
+        let document = build_dom(content.as_str());
+        let default = DensityTree::from_document(&document).unwrap();
+        let mut dtree = DensityTree::from_document(&document).unwrap();
+
+        dtree.calculate_density_tree_with(&SimpleScorer);
+        dtree.calculate_density_tree_with(&CompositeScorer);
+        for (a, b) in default.tree.values().zip(dtree.tree.values()) {
+            assert_eq!(a.density, b.density);
+        }
+    }
+}
@@ -0,0 +1,255 @@
+//! CSS selectors: the ones used across the crate, compiled once, and
+//! `css_path` building selectors pointing back at DOM nodes.
+//!
+//! Selectors from `css_path` are useful to store where content came from and
+//! re-extract it later with `scraper` alone.
+use crate::scraper::{ElementRef, Html, Selector};
+use crate::{get_node_by_id, DomExtractionError};
+use ego_tree::NodeId;
+use std::sync::LazyLock;
+
+/// Defines the crate's compiled selectors as `LazyLock` statics and lists
+/// them all in `ALL_SELECTORS`, so tests can check every one of them parses.
+macro_rules! selectors {
+    ($($(#[$meta:meta])* $name:ident = $css:expr;)*) => {
+        $(
+            $(#[$meta])*
+            #[allow(clippy::unwrap_used)]
+            pub(crate) static $name: LazyLock<Selector> =
+                LazyLock::new(|| Selector::parse($css).unwrap());
+        )*
+
+        #[cfg(test)]
+        static ALL_SELECTORS: &[(&str, &LazyLock<Selector>)] =
+            &[$((stringify!($name), &$name)),*];
+    };
+}
+
+selectors! {
+    /// `<body>` element.
+    BODY_SELECTOR = "body";
+    /// `<base>` elements with an `href`.
+    BASE_SELECTOR = "base[href]";
+    /// `<mark>` elements.
+    MARK_SELECTOR = "mark";
+    /// `<main>` elements.
+    MAIN_SELECTOR = "main";
+    /// Elements with the main landmark role.
+    ROLE_MAIN_SELECTOR = r#"[role="main"]"#;
+    /// Open Graph title meta tags.
+    OG_TITLE_SELECTOR = r#"meta[property="og:title"]"#;
+    /// `<title>` elements.
+    TITLE_SELECTOR = "title";
+    /// `<h1>` headings.
+    H1_SELECTOR = "h1";
+    /// Author meta tags, plain and Open Graph article ones.
+    AUTHOR_META_SELECTOR =
+        r#"meta[name="author"], meta[property="article:author"]"#;
+    /// Author links and byline elements in the page body.
+    BYLINE_SELECTOR =
+        r#"[rel="author"], [itemprop="author"], .byline, .author"#;
+    /// Open Graph article publication time meta tags.
+    PUBLISHED_META_SELECTOR = r#"meta[property="article:published_time"]"#;
+    /// `<time>` elements with a machine-readable `datetime`.
+    TIME_SELECTOR = "time[datetime]";
+    /// All Open Graph meta tags.
+    OG_SELECTOR = r#"meta[property^="og:"]"#;
+    /// Open Graph image meta tags.
+    OG_IMAGE_SELECTOR = r#"meta[property="og:image"]"#;
+    /// Open Graph URL meta tags.
+    OG_URL_SELECTOR = r#"meta[property="og:url"]"#;
+    /// Canonical URL links.
+    CANONICAL_SELECTOR = r#"link[rel~="canonical"]"#;
+    /// `<img>` elements.
+    IMG_SELECTOR = "img";
+    /// JSON-LD structured data scripts.
+    JSON_LD_SELECTOR = r#"script[type="application/ld+json"]"#;
+    /// Pagination links, `<link rel="next">` or anchors marked the same way.
+    NEXT_REL_SELECTOR = r#"link[rel~="next"][href], a[rel~="next"][href]"#;
+    /// Anchors with an `href`.
+    ANCHOR_SELECTOR = "a[href]";
+    /// Comment thread containers of WordPress and Disqus markup.
+    COMMENTS_SELECTOR =
+        "#comments, .comments, #disqus_thread, .disqus_thread, #respond, .respond";
+}
+
+/// Builds a CSS selector like `body > article.post > p:nth-of-type(2)`
+/// which selects exactly the element with the given `NodeId`.
+///
+/// The selector starts at `<body>` (the root element for nodes outside of
+/// it), or at the closest ancestor with an `id` unique in the document.
+/// Classes are kept for readability, `:nth-of-type` is added whenever the
+/// element has siblings with the same tag. For non-element nodes (like text)
+/// the selector points at their parent element.
+///
+/// # Examples
+///
+/// ```no_run
+/// let path = css_path(node_id, &document)?;
+/// let selector = Selector::parse(&path).unwrap();
+/// assert_eq!(document.select(&selector).count(), 1);
+/// ```
+pub fn css_path(
+    node_id: NodeId,
+    document: &Html,
+) -> Result<String, DomExtractionError> {
+    let node = get_node_by_id(node_id, document)?;
+    let mut segments = Vec::new();
+    for element in std::iter::once(node)
+        .chain(node.ancestors())
+        .filter_map(ElementRef::wrap)
+    {
+        let name = element.value().name();
+        if let Some(id) =
+            element.value().id().filter(|id| is_unique_id(id, document))
+        {
+            segments.push(format!("{}#{}", name, escape_identifier(id)));
+            break;
+        }
+        segments.push(segment(element));
+        if name == "body" {
+            break;
+        }
+    }
+    segments.reverse();
+    Ok(segments.join(" > "))
+}
+
+/// Tag name with classes, and `:nth-of-type` when the tag is not unique
+/// among siblings.
+fn segment(element: ElementRef) -> String {
+    let name = element.value().name();
+    let mut segment = name.to_string();
+    for class in element.value().classes() {
+        segment.push('.');
+        segment.push_str(&escape_identifier(class));
+    }
+
+    let same_type_siblings = || {
+        element
+            .parent()
+            .into_iter()
+            .flat_map(|parent| parent.children())
+            .filter_map(ElementRef::wrap)
+            .filter(|sibling| sibling.value().name() == name)
+    };
+    if same_type_siblings().count() > 1 {
+        let index = same_type_siblings()
+            .position(|sibling| sibling.id() == element.id())
+            .unwrap_or_default();
+        segment.push_str(&format!(":nth-of-type({})", index + 1));
+    }
+    segment
+}
+
+fn is_unique_id(id: &str, document: &Html) -> bool {
+    !id.is_empty()
+        && document
+            .tree
+            .values()
+            .filter_map(|node| node.as_element())
+            .filter(|element| element.id() == Some(id))
+            .count()
+            == 1
+}
+
+/// Escapes a string to be used as a CSS identifier (class or id).
+fn escape_identifier(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for (i, c) in value.chars().enumerate() {
+        let starts_with_digit = i == 0 && c.is_ascii_digit();
+        let starts_with_dash_digit =
+            i == 1 && c.is_ascii_digit() && value.starts_with('-');
+        if starts_with_digit || starts_with_dash_digit {
+            // digits can't start an identifier, escape as code point
+            escaped.push_str(&format!("\\{:x} ", c as u32));
+        } else if c.is_ascii_alphanumeric() || c == '-' || c == '_' || !c.is_ascii()
+        {
+            escaped.push(c);
+        } else {
+            escaped.push('\\');
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::scraper::Selector;
+    use crate::tests::{build_dom, read_file};
+
+    fn assert_selects_only(node_id: NodeId, document: &Html) -> String {
+        let path = css_path(node_id, document).unwrap();
+        let selector = Selector::parse(&path).unwrap();
+        let selected: Vec<NodeId> =
+            document.select(&selector).map(|elem| elem.id()).collect();
+        assert_eq!(selected, vec![node_id], "selector: {}", path);
+        path
+    }
+
+    #[test]
+    fn test_selectors_parse() {
+        let document = build_dom("<html><body><p>text</p></body></html>");
+        // dereferencing forces parsing, which panics on invalid selectors
+        for (name, selector) in ALL_SELECTORS {
+            let parsed = std::panic::catch_unwind(|| LazyLock::force(selector));
+            assert!(parsed.is_ok(), "invalid selector: {}", name);
+            let _ = document.select(selector).count();
+        }
+        assert_eq!(document.select(&BODY_SELECTOR).count(), 1);
+    }
+
+    #[test]
+    fn test_css_path_selects_original_node() {
+        for file_name in ["html/test_1.html", "html/article.html"] {
+            let content = read_file(file_name).unwrap();
+            let document = build_dom(content.as_str());
+            let body = document
+                .select(&Selector::parse("body").unwrap())
+                .next()
+                .unwrap();
+            for element in body.descendants().filter_map(ElementRef::wrap) {
+                assert_selects_only(element.id(), &document);
+            }
+        }
+    }
+
+    #[test]
+    fn test_css_path_format() {
+        let document = build_dom(
+            r#"<html><body>
+                <article class="post"><p>one</p><p>two</p></article>
+                <div id="sidebar"><p>side</p></div>
+                <div id="1st" class="a:b"><span>odd</span></div>
+            </body></html>"#,
+        );
+        let p = |n: usize| {
+            document
+                .select(&Selector::parse("p").unwrap())
+                .nth(n)
+                .unwrap()
+                .id()
+        };
+
+        assert_eq!(
+            assert_selects_only(p(1), &document),
+            "body > article.post > p:nth-of-type(2)"
+        );
+        assert_eq!(assert_selects_only(p(2), &document), "div#sidebar > p");
+
+        let span = document
+            .select(&Selector::parse("span").unwrap())
+            .next()
+            .unwrap();
+        assert_eq!(
+            assert_selects_only(span.id(), &document),
+            "div#\\31 st > span"
+        );
+        // text nodes point at their parent element
+        let text_id = span.first_child().unwrap().id();
+        assert_eq!(css_path(text_id, &document).unwrap(), "div#\\31 st > span");
+    }
+}
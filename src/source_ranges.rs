@@ -0,0 +1,199 @@
+//! Byte offsets of extracted text in the source HTML.
+//!
+//! `scraper` (`html5ever`) doesn't keep source positions of parsed nodes,
+//! so offsets are recomputed: text nodes of the document are matched
+//! against the source in document order, skipping markup between them.
+//! This is reliable for regular markup, but the parser's error recovery can
+//! move text around (e.g. text inside `<table>` outside of cells is moved
+//! in front of the table), such text gets a wrong range or none at all.
+use crate::scraper::Html;
+use crate::{visit_subtree_texts, ContentBlock, DensityTree, DomExtractionError};
+use ego_tree::NodeId;
+use std::collections::HashMap;
+use std::ops::Range;
+
+impl DensityTree {
+    /// Extracts the main content as blocks, as `extract_blocks` does, with
+    /// `ContentBlock::source_ranges` filled with byte ranges of the block's
+    /// text nodes in `source`, the HTML `document` was parsed from.
+    ///
+    /// A range covers the text as it is written in the source, so entities
+    /// (`&amp;`) are kept encoded and whitespace is not collapsed. Text nodes
+    /// which couldn't be found in the source get no range.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// let document = Html::parse_document(&html_string);
+    /// let mut dtree = DensityTree::from_document(&document)?;
+    /// dtree.calculate_density_sum()?;
+    /// for block in dtree.extract_blocks_with_source_ranges(&document, &html_string)? {
+    ///     for range in block.source_ranges {
+    ///         println!("{}", &html_string[range]);
+    ///     }
+    /// }
+    /// ```
+    pub fn extract_blocks_with_source_ranges(
+        &self,
+        document: &Html,
+        source: &str,
+    ) -> Result<Vec<ContentBlock>, DomExtractionError> {
+        let mut blocks = self.extract_blocks(document)?;
+        let text_ranges = text_source_ranges(document, source);
+        let content_nodes: HashMap<NodeId, _> = self
+            .content_nodes()
+            .into_iter()
+            .map(|node| (node.value().node_id, node))
+            .collect();
+        for block in &mut blocks {
+            let Some(node) = content_nodes.get(&block.node_id) else {
+                continue;
+            };
+            visit_subtree_texts(*node, document, &mut |text_node, _| {
+                if let Some(range) = text_ranges.get(&text_node.id()) {
+                    block.source_ranges.push(range.clone());
+                }
+            })?;
+        }
+        Ok(blocks)
+    }
+}
+
+/// Finds byte ranges of all non-blank text nodes of the document in the
+/// source, trimmed like the texts themselves.
+fn text_source_ranges(
+    document: &Html,
+    source: &str,
+) -> HashMap<NodeId, Range<usize>> {
+    let mut ranges = HashMap::new();
+    let mut cursor = 0;
+    for node in document.tree.root().descendants() {
+        let Some(text) = node.value().as_text() else {
+            continue;
+        };
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let start = skip_markup(source, cursor);
+        if start >= source.len() {
+            break;
+        }
+        let end = if source[start..].starts_with(text) {
+            start + text.len()
+        } else {
+            // entities are decoded in the DOM, but not in the source, the
+            // text then runs until the next tag
+            let raw = source[start..]
+                .find('<')
+                .map_or(source.len(), |i| start + i);
+            start + source[start..raw].trim_end().len()
+        };
+        ranges.insert(node.id(), start..end);
+        cursor = end;
+    }
+    ranges
+}
+
+/// Returns position of the first char after `from` which is neither
+/// whitespace nor part of a tag, comment or doctype.
+fn skip_markup(source: &str, from: usize) -> usize {
+    let bytes = source.as_bytes();
+    let mut pos = from;
+    loop {
+        while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        let rest = &source[pos..];
+        if rest.starts_with("<!--") {
+            pos = rest.find("-->").map_or(source.len(), |i| pos + i + 3);
+        } else if is_tag_start(rest) {
+            pos = tag_end(source, pos);
+        } else {
+            return pos;
+        }
+    }
+}
+
+/// Whether `<` at the start of the text opens a tag rather than being a
+/// literal `<` of the text (as in `a < b`).
+fn is_tag_start(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars.next() == Some('<')
+        && chars.next().is_some_and(|c| {
+            c.is_ascii_alphabetic() || matches!(c, '/' | '!' | '?')
+        })
+}
+
+/// Returns position right after `>` of the tag starting at `start`, quoted
+/// attribute values may contain `>`.
+fn tag_end(source: &str, start: usize) -> usize {
+    let mut quote = None;
+    for (i, c) in source[start..].char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '>') => return start + i + 1,
+            _ => {}
+        }
+    }
+    source.len()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::tests::{build_dom, read_file};
+    use crate::unicode;
+
+    #[test]
+    fn test_extract_blocks_with_source_ranges() {
+        let source = read_file("html/article.html").unwrap();
+        let document = build_dom(source.as_str());
+        let mut dtree = DensityTree::from_document(&document).unwrap();
+        dtree.calculate_density_sum().unwrap();
+
+        let blocks = dtree
+            .extract_blocks_with_source_ranges(&document, &source)
+            .unwrap();
+        assert!(!blocks.is_empty());
+        for block in &blocks {
+            let sliced: Vec<&str> = block
+                .source_ranges
+                .iter()
+                .map(|range| &source[range.clone()])
+                .collect();
+            assert_eq!(
+                unicode::normalize_text(&sliced.join(" ")),
+                unicode::normalize_text(&block.text)
+            );
+        }
+        let content = dtree.extract_content(&document).unwrap();
+        assert!(content.contains("lithium deposits"));
+        assert!(blocks
+            .iter()
+            .flat_map(|block| &block.source_ranges)
+            .any(|range| source[range.clone()].contains("lithium deposits")));
+    }
+
+    #[test]
+    fn test_text_source_ranges_markup() {
+        let source = r#"<html><head><title>Home</title></head><body>
+            <!-- Home -->
+            <a title="Home > index" href="/">Home</a>
+            <p>Fish &amp; chips</p><p>a < b</p></body></html>"#;
+        let document = build_dom(source);
+        let ranges = text_source_ranges(&document, source);
+
+        let mut sliced: Vec<&str> = ranges
+            .values()
+            .map(|range| &source[range.clone()])
+            .collect();
+        sliced.sort();
+        assert_eq!(sliced, vec!["Fish &amp; chips", "Home", "Home", "a < b"]);
+
+        let anchor = source.find(">Home</a>").unwrap() + 1;
+        assert!(ranges.values().any(|range| range.start == anchor));
+    }
+}
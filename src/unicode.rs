@@ -0,0 +1,497 @@
+//! Unicode helpers for analysing extracted text.
+use std::borrow::Cow;
+use unicode_normalization::UnicodeNormalization;
+use unicode_script::{Script, UnicodeScript};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Common English words skipped by `keyword_frequencies`.
+const STOPWORDS: &[&str] = &[
+    "a", "about", "after", "all", "also", "an", "and", "any", "are", "as", "at",
+    "be", "been", "but", "by", "can", "could", "do", "for", "from", "had", "has",
+    "have", "he", "her", "his", "i", "if", "in", "into", "is", "it", "its", "more",
+    "my", "no", "not", "of", "on", "one", "or", "our", "out", "she", "so", "than",
+    "that", "the", "their", "them", "then", "there", "these", "they", "this", "to",
+    "up", "was", "we", "were", "what", "when", "which", "who", "will", "with",
+    "would", "you", "your",
+];
+
+/// Abbreviations which don't end a sentence even before a capitalized word,
+/// lowercased.
+const ABBREVIATIONS: &[&str] = &[
+    "dr.", "e.g.", "i.e.", "jr.", "mr.", "mrs.", "ms.", "no.", "prof.", "sr.",
+    "st.", "vs.",
+];
+
+/// Reading speed of an average adult, used for reading time estimates.
+pub const DEFAULT_WORDS_PER_MINUTE: u32 = 200;
+
+const ZERO_WIDTH_SPACE: char = '\u{200B}';
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+
+/// Invisible chars which only affect rendering: zero-width space and bidi
+/// marks, embeddings and overrides.
+fn is_invisible_char(c: char) -> bool {
+    matches!(
+        c,
+        ZERO_WIDTH_SPACE | '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}'
+    )
+}
+
+/// Removes zero-width spaces, bidi control chars and zero-width joiners
+/// which don't join anything. A joiner is kept when it's followed by another
+/// char of the same grapheme cluster, as in emoji sequences like `"👨‍👩‍👧"`.
+fn strip_invisible(text: &str) -> Cow<'_, str> {
+    if !text
+        .chars()
+        .any(|c| c == ZERO_WIDTH_JOINER || is_invisible_char(c))
+    {
+        return Cow::Borrowed(text);
+    }
+    let mut stripped = String::with_capacity(text.len());
+    for grapheme in text.graphemes(true) {
+        let mut chars = grapheme.chars().peekable();
+        while let Some(c) = chars.next() {
+            let dangling_joiner = c == ZERO_WIDTH_JOINER && chars.peek().is_none();
+            if !dangling_joiner && !is_invisible_char(c) {
+                stripped.push(c);
+            }
+        }
+    }
+    Cow::Owned(stripped)
+}
+
+/// Collapses whitespace runs into single spaces, trims the text and applies
+/// Unicode NFC normalization, so texts which only differ in whitespace or in
+/// composed/decomposed forms of chars (`"é"` vs `"e\u{301}"`) compare equal.
+/// Zero-width spaces and bidi control chars are removed.
+///
+/// `scraper` decodes entities while parsing, but double-escaped markup
+/// leaves entities like `&eacute;` or `&#233;` in text nodes, these are
+/// decoded too. Non-breaking spaces are treated as regular whitespace.
+///
+/// Case and punctuation are kept as is.
+pub fn normalize_text(text: &str) -> String {
+    normalize_text_with(text, NormalizationForm::Nfc)
+}
+
+/// Unicode normalization form applied by `normalize_text_with`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NormalizationForm {
+    /// Canonical composition, `"e\u{301}"` becomes `"é"`.
+    #[default]
+    Nfc,
+    /// Canonical decomposition, `"é"` becomes `"e\u{301}"`.
+    Nfd,
+    /// Compatibility composition, also folds ligatures and full-width
+    /// chars (`"ﬁ"` becomes `"fi"`, `"１"` becomes `"1"`). Handy for indexing.
+    Nfkc,
+    /// Compatibility decomposition.
+    Nfkd,
+}
+
+/// Same as `normalize_text`, but with the given Unicode normalization form.
+///
+/// # Examples
+///
+/// ```no_run
+/// let text = normalize_text_with("１２３", NormalizationForm::Nfkc);
+/// assert_eq!(text, "123");
+/// ```
+pub fn normalize_text_with(text: &str, form: NormalizationForm) -> String {
+    let text = match text.contains('&') {
+        true => html_escape::decode_html_entities(text),
+        false => Cow::Borrowed(text),
+    };
+    let text = strip_invisible(&text)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    match form {
+        NormalizationForm::Nfc => text.nfc().collect(),
+        NormalizationForm::Nfd => text.nfd().collect(),
+        NormalizationForm::Nfkc => text.nfkc().collect(),
+        NormalizationForm::Nfkd => text.nfkd().collect(),
+    }
+}
+
+/// Counts user-perceived chars (extended grapheme clusters) of the text, so
+/// `"e\u{301}"` and `"👍🏽"` count as one each.
+///
+/// All-ASCII text, the common case for Western content, is counted by its
+/// length without running the segmentation, since every ASCII byte is a
+/// grapheme of its own (except for `"\r\n"`, which is a single grapheme).
+/// Zero-width spaces and bidi control chars are not counted.
+pub fn count_graphemes(text: &str) -> u32 {
+    if text.is_ascii() {
+        let crlf_count = text.matches("\r\n").count();
+        return (text.len() - crlf_count) as u32;
+    }
+    strip_invisible(text).graphemes(true).count() as u32
+}
+
+/// Returns the display width of the text in terminal columns: East Asian
+/// wide and full-width chars (CJK ideographs, kana, hangul) take two columns,
+/// most other chars one, combining marks and zero-width chars none.
+///
+/// # Examples
+///
+/// ```no_run
+/// assert_eq!(count_display_width("你好"), 4);
+/// ```
+pub fn count_display_width(text: &str) -> u32 {
+    strip_invisible(text).width() as u32
+}
+
+/// Returns the name of the dominant Unicode script of the text, such as
+/// `"Latin"`, `"Cyrillic"`, `"Han"`, `"Arabic"` or `"Devanagari"`.
+///
+/// Every char is tallied by its `Script` property, chars shared between
+/// scripts (`Common`: digits, punctuation, spaces; `Inherited`: combining
+/// marks) are ignored. Ties go to the script seen first. Returns
+/// `"Unknown"` when the text has no script-specific chars at all.
+///
+/// # Examples
+///
+/// ```no_run
+/// assert_eq!(detect_primary_script("Привет, мир!"), "Cyrillic");
+/// ```
+pub fn detect_primary_script(text: &str) -> &'static str {
+    let mut counts: Vec<(Script, usize)> = Vec::new();
+    for script in text.chars().map(|c| c.script()) {
+        if matches!(script, Script::Common | Script::Inherited | Script::Unknown) {
+            continue;
+        }
+        match counts.iter_mut().find(|(s, _)| *s == script) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((script, 1)),
+        }
+    }
+
+    let mut primary = (Script::Unknown, 0);
+    for (script, count) in counts {
+        if count > primary.1 {
+            primary = (script, count);
+        }
+    }
+    primary.0.full_name()
+}
+
+/// Returns the `top_n` most frequent words of the text with their counts,
+/// most frequent first, ties in order of the first occurrence.
+///
+/// Words are split on Unicode word boundaries and lowercased, common English
+/// stopwords are dropped. Text without any whitespace (Chinese, Japanese,
+/// Thai) is split into graphemes instead, since word boundaries of such
+/// scripts can't be found without a dictionary.
+///
+/// # Examples
+///
+/// ```no_run
+/// let keywords = keyword_frequencies(&content, 10);
+/// ```
+pub fn keyword_frequencies(text: &str, top_n: usize) -> Vec<(String, u32)> {
+    let text = text.to_lowercase();
+    let tokens: Vec<&str> = if text.contains(char::is_whitespace) {
+        text.unicode_words().collect()
+    } else {
+        text.graphemes(true)
+            .filter(|grapheme| grapheme.chars().any(char::is_alphanumeric))
+            .collect()
+    };
+
+    let mut counts: Vec<(String, u32)> = Vec::new();
+    let mut positions: std::collections::HashMap<&str, usize> =
+        std::collections::HashMap::new();
+    for token in tokens {
+        if STOPWORDS.contains(&token) {
+            continue;
+        }
+        match positions.get(token) {
+            Some(&position) => counts[position].1 += 1,
+            None => {
+                positions.insert(token, counts.len());
+                counts.push((token.to_string(), 1));
+            }
+        }
+    }
+    // stable sort keeps ties in order of the first occurrence
+    counts.sort_by(|(_, a), (_, b)| b.cmp(a));
+    counts.truncate(top_n);
+    counts
+}
+
+/// Estimates how long reading the text takes at `wpm` words per minute,
+/// see `DEFAULT_WORDS_PER_MINUTE`.
+///
+/// Words are counted on Unicode word boundaries, which also makes every CJK
+/// ideograph a word of its own. Text without any whitespace is counted in
+/// graphemes instead, as in `keyword_frequencies`. Empty text (or zero
+/// `wpm`) takes no time.
+///
+/// # Examples
+///
+/// ```no_run
+/// let minutes = reading_time_minutes(&content, DEFAULT_WORDS_PER_MINUTE);
+/// println!("{} min read", minutes.ceil());
+/// ```
+pub fn reading_time_minutes(text: &str, wpm: u32) -> f32 {
+    if wpm == 0 {
+        return 0.0;
+    }
+    let word_count = if text.contains(char::is_whitespace) {
+        text.unicode_words().count()
+    } else {
+        text.graphemes(true)
+            .filter(|grapheme| grapheme.chars().any(char::is_alphanumeric))
+            .count()
+    };
+    word_count as f32 / wpm as f32
+}
+
+/// Returns the first `n` sentences of the text, such as the output of
+/// `extract_content`.
+///
+/// Sentences are split on Unicode sentence boundaries, sentences ending with
+/// a common abbreviation (`e.g.`, `Dr.`, `vs.`) are joined with the next one.
+/// The whole text is returned when it has `n` sentences or less.
+///
+/// # Examples
+///
+/// ```no_run
+/// let preview = first_sentences(&content, 2);
+/// ```
+pub fn first_sentences(text: &str, n: usize) -> String {
+    let mut result = String::new();
+    let mut count = 0;
+    for segment in text.split_sentence_bounds() {
+        if count == n {
+            break;
+        }
+        result.push_str(segment);
+        if !ends_with_abbreviation(segment) {
+            count += 1;
+        }
+    }
+    result.trim().to_string()
+}
+
+/// Counts sentences of the text, split the same way as in `first_sentences`.
+///
+/// Blank segments (trailing whitespace) are not counted, text without
+/// terminal punctuation is a single sentence.
+///
+/// # Examples
+///
+/// ```no_run
+/// assert_eq!(sentence_count("Ask Dr. Smith. He knows."), 2);
+/// ```
+pub fn sentence_count(text: &str) -> u32 {
+    let mut count = 0;
+    let mut continued = false;
+    for segment in text.split_sentence_bounds() {
+        if segment.trim().is_empty() {
+            continue;
+        }
+        if !continued {
+            count += 1;
+        }
+        continued = ends_with_abbreviation(segment);
+    }
+    count
+}
+
+fn ends_with_abbreviation(segment: &str) -> bool {
+    segment
+        .split_whitespace()
+        .next_back()
+        .is_some_and(|word| ABBREVIATIONS.contains(&word.to_lowercase().as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_text() {
+        assert_eq!(normalize_text("  Hello \t\n world  "), "Hello world");
+        assert_eq!(normalize_text("caf\u{65}\u{301}"), "caf\u{e9}");
+        assert_eq!(normalize_text("Hello, World"), "Hello, World");
+    }
+
+    #[test]
+    fn test_count_graphemes() {
+        let segmented = |text: &str| text.graphemes(true).count() as u32;
+        for text in [
+            "",
+            "Hello, world!",
+            "line\r\nbreak\r\n",
+            "caf\u{65}\u{301}",
+            "Привет, мир!",
+            "你好，世界",
+            "👍🏽 👨‍👩‍👧",
+        ] {
+            assert_eq!(count_graphemes(text), segmented(text), "{:?}", text);
+        }
+        assert_eq!(count_graphemes("Hello"), 5);
+        assert_eq!(count_graphemes("caf\u{65}\u{301}"), 4);
+    }
+
+    #[test]
+    fn test_reading_time_minutes() {
+        let text = "lithium demand keeps growing ".repeat(100);
+        let minutes = reading_time_minutes(&text, DEFAULT_WORDS_PER_MINUTE);
+        assert!((minutes - 2.0).abs() < 0.01, "{minutes}");
+        assert_eq!(reading_time_minutes(&text, 400), minutes / 2.0);
+
+        assert_eq!(reading_time_minutes("", DEFAULT_WORDS_PER_MINUTE), 0.0);
+        assert_eq!(reading_time_minutes("   ", DEFAULT_WORDS_PER_MINUTE), 0.0);
+        assert_eq!(reading_time_minutes(&text, 0), 0.0);
+        // counted per char, punctuation aside
+        assert_eq!(reading_time_minutes("你好，世界", 4), 1.0);
+    }
+
+    #[test]
+    fn test_sentence_count() {
+        assert_eq!(sentence_count("Prices rose. Miners rushed in!  \n"), 2);
+        assert_eq!(sentence_count("no punctuation at all"), 1);
+        assert_eq!(sentence_count("Ask Dr. Smith. He knows."), 2);
+        assert_eq!(sentence_count("Meet me on Main St."), 1);
+        assert_eq!(sentence_count(""), 0);
+        assert_eq!(sentence_count(" \t\n"), 0);
+    }
+
+    #[test]
+    fn test_normalize_text_with() {
+        let digits = "\u{ff11}\u{ff12}\u{ff13}";
+        assert_eq!(normalize_text_with(digits, NormalizationForm::Nfkc), "123");
+        assert_eq!(normalize_text_with(digits, NormalizationForm::Nfc), digits);
+        assert_eq!(normalize_text(digits), digits);
+        assert_eq!(
+            normalize_text_with("caf\u{e9}", NormalizationForm::Nfd),
+            "caf\u{65}\u{301}"
+        );
+        assert_eq!(
+            normalize_text_with("\u{fb01}ne", NormalizationForm::Nfkd),
+            "fine"
+        );
+    }
+
+    #[test]
+    fn test_normalize_text_entities() {
+        assert_eq!(normalize_text("caf&eacute;"), "caf\u{e9}");
+        assert_eq!(normalize_text("a&nbsp;b"), "a b");
+        assert_eq!(normalize_text("a\u{a0}b"), "a b");
+        assert_eq!(
+            normalize_text("&#233;t&#xE9; &amp; more"),
+            "\u{e9}t\u{e9} & more"
+        );
+        assert_eq!(normalize_text("AT&T"), "AT&T");
+    }
+
+    #[test]
+    fn test_invisible_chars() {
+        let clean = "Lithium rush";
+        let padded = "\u{200B}Lithium\u{200B} \u{200E}rush\u{202C}\u{200B}";
+        assert_eq!(count_graphemes(padded), count_graphemes(clean));
+        assert_eq!(count_display_width(padded), count_display_width(clean));
+        assert_eq!(normalize_text(padded), clean);
+
+        // joiner of an emoji sequence is kept, a dangling one is dropped
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        assert_eq!(count_graphemes(family), 1);
+        assert_eq!(normalize_text(family), family);
+        assert_eq!(normalize_text("a\u{200D} b"), "a b");
+    }
+
+    #[test]
+    fn test_count_display_width() {
+        let text = "锂价格下跌，电池需求增长";
+        assert_eq!(count_display_width(text), 2 * count_graphemes(text));
+        assert_eq!(count_display_width("Hello"), 5);
+        assert_eq!(count_display_width("caf\u{65}\u{301}"), 4);
+    }
+
+    #[test]
+    fn test_keyword_frequencies() {
+        let text = "Lithium prices fell. The lithium market is volatile, and \
+                    the demand for lithium batteries keeps growing. Battery \
+                    makers watch prices.";
+        let keywords = keyword_frequencies(text, 3);
+        assert_eq!(
+            keywords,
+            vec![
+                ("lithium".to_string(), 3),
+                ("prices".to_string(), 2),
+                ("fell".to_string(), 1),
+            ]
+        );
+        assert!(keyword_frequencies(text, 100)
+            .iter()
+            .all(|(word, _)| !STOPWORDS.contains(&word.as_str())));
+        assert!(keyword_frequencies(text, 0).is_empty());
+    }
+
+    #[test]
+    fn test_keyword_frequencies_cjk() {
+        let keywords = keyword_frequencies("锂价格下跌，锂电池需求增长。", 1);
+        assert_eq!(keywords, vec![("锂".to_string(), 2)]);
+    }
+
+    #[test]
+    fn test_first_sentences() {
+        let text = "Miners are racing to secure deposits. Demand for batteries, \
+                    e.g. for cars, keeps growing! Dr. Smith expects prices to \
+                    double. Will they?";
+        assert_eq!(
+            first_sentences(text, 2),
+            "Miners are racing to secure deposits. Demand for batteries, \
+             e.g. for cars, keeps growing!"
+        );
+        assert_eq!(
+            first_sentences(text, 3),
+            "Miners are racing to secure deposits. Demand for batteries, \
+             e.g. for cars, keeps growing! Dr. Smith expects prices to double."
+        );
+        assert_eq!(first_sentences(text, 10), text);
+        assert_eq!(first_sentences(text, 0), "");
+    }
+
+    #[test]
+    fn test_detect_primary_script_basic() {
+        assert_eq!(detect_primary_script("Hello, world!"), "Latin");
+        assert_eq!(detect_primary_script("Привет, мир!"), "Cyrillic");
+        assert_eq!(detect_primary_script("你好，世界"), "Han");
+    }
+
+    #[test]
+    fn test_detect_primary_script_arabic() {
+        assert_eq!(detect_primary_script("مرحبا بالعالم"), "Arabic");
+    }
+
+    #[test]
+    fn test_detect_primary_script_greek() {
+        assert_eq!(detect_primary_script("Γειά σου Κόσμε"), "Greek");
+    }
+
+    #[test]
+    fn test_detect_primary_script_devanagari() {
+        assert_eq!(detect_primary_script("नमस्ते दुनिया"), "Devanagari");
+    }
+
+    #[test]
+    fn test_detect_primary_script_mixed() {
+        // mostly Hebrew with a short Latin brand name
+        assert_eq!(
+            detect_primary_script("שלום עולם, ברוכים הבאים ל-ACME"),
+            "Hebrew"
+        );
+        assert_eq!(detect_primary_script("Thai word: สวัสดี in text"), "Latin");
+    }
+
+    #[test]
+    fn test_detect_primary_script_no_letters() {
+        assert_eq!(detect_primary_script(""), "Unknown");
+        assert_eq!(detect_primary_script("123 !?"), "Unknown");
+    }
+}